@@ -0,0 +1,51 @@
+//! Compares sorting with the discriminant-first `Ord` of `Fungible` against the structural
+//! comparison it replaced.
+use std::cmp::Ordering;
+use cosmwasm_std::Addr;
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion};
+use cw_fungible::Fungible;
+
+/// The structural comparison preceding the discriminant-first `Ord` of `Fungible`.
+fn naive_cmp(a: &Fungible, b: &Fungible) -> Ordering {
+  match (a, b) {
+    (Fungible::Coin(a), Fungible::Coin(b)) => a.as_str().cmp(b.as_str()),
+    (Fungible::Token(a), Fungible::Token(b)) => a.as_str().cmp(b.as_str()),
+    (Fungible::Coin(_), Fungible::Token(_)) => Ordering::Greater,
+    (Fungible::Token(_), Fungible::Coin(_)) => Ordering::Less,
+  }
+}
+
+/// Mixed coins and tokens sharing long common prefixes, in a deterministic shuffled order.
+fn shuffled_assets(n: usize) -> Vec<Fungible> {
+  let mut seed = 0x2545_f491_4f6c_dd1du64;
+  (0..n)
+    .map(|_| {
+      seed ^= seed << 13;
+      seed ^= seed >> 7;
+      seed ^= seed << 17;
+      let inner = format!("ibc/27394FB092D2ECCD56123C74F36E4C1F926001CE{:016x}", seed);
+      if seed & 1 == 0 { Fungible::Coin(inner) } else { Fungible::Token(Addr::unchecked(inner)) }
+    })
+    .collect()
+}
+
+fn bench_sort(c: &mut Criterion) {
+  let assets = shuffled_assets(10_000);
+  
+  // both through `sort_by`, as `sort` goes through `PartialOrd::lt` and would skew the comparison
+  let mut group = c.benchmark_group("sort_10k");
+  group.bench_function("ord", |b| b.iter_batched(
+    || assets.clone(),
+    |mut assets| { assets.sort_by(Fungible::cmp); black_box(assets) },
+    BatchSize::LargeInput,
+  ));
+  group.bench_function("naive", |b| b.iter_batched(
+    || assets.clone(),
+    |mut assets| { assets.sort_by(naive_cmp); black_box(assets) },
+    BatchSize::LargeInput,
+  ));
+  group.finish();
+}
+
+criterion_group!(benches, bench_sort);
+criterion_main!(benches);
@@ -0,0 +1,26 @@
+//! Generates the `schema/` JSON of a contract API using `Fungible` in its messages.
+//! 
+//! Run with `cargo run --example schema`.
+
+use cosmwasm_schema::{cw_serde, write_api};
+use cw_fungible::{Asset, Fungible};
+
+#[cw_serde]
+pub struct InstantiateMsg {
+  pub accepted: Vec<Fungible>,
+}
+
+#[cw_serde]
+pub enum ExecuteMsg {
+  Swap {
+    offer: Asset,
+    ask: Fungible,
+  },
+}
+
+fn main() {
+  write_api! {
+    instantiate: InstantiateMsg,
+    execute: ExecuteMsg,
+  }
+}
@@ -0,0 +1,200 @@
+use std::str::FromStr;
+use cosmwasm_std::{Addr, Api, StdError, StdResult};
+use crate::{Fungible, FungibleError};
+
+impl Fungible {
+  /// Canonicalizes the casing of a bech32 token address, which is lowercase by spec. The
+  /// human-readable prefix is simply lowercased, whereas a data part mixing cases, or a bad
+  /// checksum, is rejected as invalid bech32. Coins pass through unchanged, as denoms are
+  /// case-sensitive.
+  /// 
+  /// Intended to be applied right after deserializing untrusted input.
+  pub fn canonicalize_casing(self) -> Result<Fungible, FungibleError> {
+    match self {
+      Fungible::Coin(_) => Ok(self),
+      Fungible::Token(token) => {
+        let invalid = || FungibleError::InvalidAddress { address: token.to_string() };
+        let (hrp, data) = token.as_str().rsplit_once('1').ok_or_else(invalid)?;
+        
+        let has_lower = data.chars().any(|c| c.is_ascii_lowercase());
+        let has_upper = data.chars().any(|c| c.is_ascii_uppercase());
+        if hrp.is_empty() || (has_lower && has_upper) {
+          return Err(invalid());
+        }
+        
+        let canonical = format!("{}1{}", hrp.to_ascii_lowercase(), data.to_ascii_lowercase());
+        bech32::decode(&canonical).map_err(|_| invalid())?;
+        Ok(Fungible::Token(Addr::unchecked(canonical)))
+      },
+    }
+  }
+  
+  /// Lowercases a token address, the canonical form of bech32, so sloppy casing cannot produce
+  /// two distinct keys for the same account. Coins pass through unchanged, as denoms are
+  /// case-sensitive.
+  /// 
+  /// Mixed-case addresses, albeit invalid bech32, are lowercased as well. Use
+  /// `canonicalize_casing` to reject them instead.
+  pub fn normalize(self) -> Fungible {
+    match self {
+      Fungible::Coin(_) => self,
+      Fungible::Token(token) => Fungible::Token(Addr::unchecked(token.as_str().to_ascii_lowercase())),
+    }
+  }
+  
+  /// Human-readable prefix of a bech32 token address, e.g. `terra`, or `None` for coins and
+  /// addresses which are not valid bech32.
+  pub fn token_hrp(&self) -> Option<String> {
+    match self {
+      Fungible::Coin(_) => None,
+      Fungible::Token(token) => bech32::decode(token.as_str()).ok().map(|(hrp, _, _)| hrp),
+    }
+  }
+  
+  /// Re-validates a token address against `api`, e.g. when migrating state across a chain upgrade
+  /// changing the address encoding. Coins pass through unchanged.
+  pub fn revalidate(self, api: &dyn Api) -> StdResult<Fungible> {
+    match self {
+      Fungible::Coin(_) => Ok(self),
+      Fungible::Token(token) => api.addr_validate(token.as_str())
+        .map(Fungible::Token)
+        .map_err(|_| FungibleError::InvalidAddress { address: token.into_string() }.into()),
+    }
+  }
+  
+  /// Parses like `FromStr`, but additionally rejects a coin whose denom validates as an address and
+  /// a token whose address does not, as `FungibleError::SuspiciousClassification`. Only as strict as
+  /// the address validation of `api`.
+  pub fn from_str_strict(s: &str, api: &dyn Api) -> StdResult<Fungible> {
+    let fungible = Fungible::from_str(s).map_err(StdError::generic_err)?;
+    let suspicious = match &fungible {
+      Fungible::Coin(coin) => api.addr_validate(coin).is_ok(),
+      Fungible::Token(token) => api.addr_validate(token.as_str()).is_err(),
+    };
+    
+    if suspicious {
+      return Err(FungibleError::SuspiciousClassification { fungible: fungible.to_string() }.into());
+    }
+    Ok(fungible)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use cosmwasm_std::testing::MockApi;
+  
+  /// A valid bech32 address of `hrp`.
+  fn bech32_address(hrp: &str) -> String {
+    use bech32::{ToBase32, Variant};
+    bech32::encode(hrp, [1u8; 20].to_base32(), Variant::Bech32).unwrap()
+  }
+  
+  #[test]
+  fn test_canonicalize_casing() {
+    let address = bech32_address("terra");
+    let canonical = Fungible::Token(Addr::unchecked(&address));
+    let (_, data) = address.split_once('1').unwrap();
+    
+    assert_eq!(canonical.clone().canonicalize_casing().unwrap(), canonical);
+    assert_eq!(Fungible::Token(Addr::unchecked(format!("TERRA1{}", data))).canonicalize_casing().unwrap(), canonical);
+    assert_eq!(Fungible::Token(Addr::unchecked(address.to_ascii_uppercase())).canonicalize_casing().unwrap(), canonical);
+    
+    let coin = Fungible::Coin("uLUNA".to_string());
+    assert_eq!(coin.clone().canonicalize_casing().unwrap(), coin);
+  }
+  
+  #[test]
+  fn test_canonicalize_casing_invalid() {
+    let address = bech32_address("terra");
+    
+    // mixed-case data part
+    let mixed = format!("{}{}", &address[..8], address[8..].to_ascii_uppercase());
+    assert_eq!(
+      Fungible::Token(Addr::unchecked(&mixed)).canonicalize_casing(),
+      Err(FungibleError::InvalidAddress { address: mixed.clone() }),
+    );
+    
+    // bad checksum, whatever the casing
+    let last = if address.ends_with('q') { 'p' } else { 'q' };
+    let corrupted = format!("{}{}", &address[..address.len() - 1], last);
+    assert!(Fungible::Token(Addr::unchecked(&corrupted)).canonicalize_casing().is_err());
+    assert!(Fungible::Token(Addr::unchecked(corrupted.to_ascii_uppercase())).canonicalize_casing().is_err());
+    
+    assert!(Fungible::Token(Addr::unchecked("terra")).canonicalize_casing().is_err());
+    assert!(Fungible::Token(Addr::unchecked("terra1qqqsyqcyq5rqwzqf")).canonicalize_casing().is_err());
+  }
+  
+  #[test]
+  fn test_normalize() {
+    let canonical = Fungible::Token(Addr::unchecked("terra1qqqsyqcyq5rqwzqf"));
+    
+    assert_eq!(canonical.clone().normalize(), canonical);
+    assert_eq!(Fungible::Token(Addr::unchecked("TERRA1QQQSYQCYQ5RQWZQF")).normalize(), canonical);
+    assert_eq!(Fungible::Token(Addr::unchecked("terra1qqqSYQcyq5rqwzqf")).normalize(), canonical);
+    
+    let coin = Fungible::Coin("uLUNA".to_string());
+    assert_eq!(coin.clone().normalize(), coin);
+  }
+  
+  #[test]
+  fn test_token_hrp() {
+    use bech32::{ToBase32, Variant};
+    
+    let address = bech32::encode("juno", [1u8; 20].to_base32(), Variant::Bech32).unwrap();
+    assert_eq!(Fungible::Token(Addr::unchecked(address.clone())).token_hrp(), Some("juno".to_string()));
+    
+    // bad checksum
+    let last = if address.ends_with('q') { 'p' } else { 'q' };
+    let corrupted = format!("{}{}", &address[..address.len() - 1], last);
+    assert_eq!(Fungible::Token(Addr::unchecked(corrupted)).token_hrp(), None);
+    assert_eq!(Fungible::Token(Addr::unchecked("token")).token_hrp(), None);
+    assert_eq!(Fungible::Coin("uluna".to_string()).token_hrp(), None);
+  }
+  
+  #[test]
+  fn test_revalidate() {
+    let api = MockApi::default();
+    let assets = vec![
+      Fungible::Coin("uLUNA".to_string()),
+      Fungible::Token(Addr::unchecked("token1")),
+      Fungible::Token(Addr::unchecked("token2")),
+    ];
+    
+    let migrated: StdResult<Vec<_>> = assets.clone().into_iter().map(|asset| asset.revalidate(&api)).collect();
+    assert_eq!(migrated.unwrap(), assets);
+    
+    let invalid = vec![
+      Fungible::Token(Addr::unchecked("token1")),
+      Fungible::Token(Addr::unchecked("TOKEN2")),
+    ];
+    let migrated: StdResult<Vec<_>> = invalid.into_iter().map(|asset| asset.revalidate(&api)).collect();
+    assert_eq!(migrated.unwrap_err(), StdError::generic_err("Invalid address: TOKEN2"));
+  }
+  
+  #[test]
+  fn test_from_str_strict() {
+    let api = MockApi::default();
+    
+    assert_eq!(Fungible::from_str_strict("Coin(ibc/ABC)", &api).unwrap(), Fungible::Coin("ibc/ABC".to_string()));
+    assert_eq!(Fungible::from_str_strict("Token(token)", &api).unwrap(), Fungible::Token(Addr::unchecked("token")));
+    assert!(Fungible::from_str_strict("ibc/ABC", &api).is_err());
+  }
+  
+  #[test]
+  fn test_from_str_strict_suspicious() {
+    let api = MockApi::default();
+    
+    assert_eq!(
+      Fungible::from_str_strict("Coin(cosmos1token)", &api).unwrap_err(),
+      StdError::generic_err("Suspicious classification: Coin(cosmos1token)"),
+    );
+    assert_eq!(
+      Fungible::from_str_strict("Token(uatom/IBC)", &api).unwrap_err(),
+      StdError::generic_err("Suspicious classification: Token(uatom/IBC)"),
+    );
+    
+    // plain parsing stays permissive
+    assert!(Fungible::from_str("Coin(cosmos1token)").is_ok());
+  }
+}
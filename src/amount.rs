@@ -0,0 +1,49 @@
+use std::cmp::Ordering;
+use cosmwasm_std::{StdResult, Uint128};
+use crate::Fungible;
+
+impl Fungible {
+  /// Scales `amount` of this asset from `from_decimals` to `to_decimals`, e.g. to compare amounts of
+  /// assets with different decimals in a common base.
+  /// 
+  /// Upscaling errors on overflow. Downscaling truncates, i.e. rounds towards zero.
+  pub fn scale_to(&self, amount: Uint128, from_decimals: u8, to_decimals: u8) -> StdResult<Uint128> {
+    let ten = Uint128::new(10);
+    match to_decimals.cmp(&from_decimals) {
+      Ordering::Equal => Ok(amount),
+      Ordering::Greater => {
+        let factor = ten.checked_pow((to_decimals - from_decimals) as u32)?;
+        Ok(amount.checked_mul(factor)?)
+      },
+      Ordering::Less => match ten.checked_pow((from_decimals - to_decimals) as u32) {
+        Ok(factor) => Ok(amount / factor),
+        // factor exceeds any amount
+        Err(_) => Ok(Uint128::zero()),
+      },
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  
+  #[test]
+  fn test_scale_to() {
+    let coin = Fungible::Coin("uluna".to_string());
+    
+    assert_eq!(coin.scale_to(Uint128::new(1_234), 6, 6).unwrap(), Uint128::new(1_234));
+    assert_eq!(coin.scale_to(Uint128::new(1_234), 6, 18).unwrap(), Uint128::new(1_234_000_000_000_000));
+    assert_eq!(coin.scale_to(Uint128::new(1_234_567), 6, 3).unwrap(), Uint128::new(1_234));
+    assert_eq!(coin.scale_to(Uint128::new(999), 6, 3).unwrap(), Uint128::zero());
+    assert_eq!(coin.scale_to(Uint128::MAX, 255, 0).unwrap(), Uint128::zero());
+  }
+  
+  #[test]
+  fn test_scale_to_overflow() {
+    let coin = Fungible::Coin("uluna".to_string());
+    
+    assert!(coin.scale_to(Uint128::MAX, 0, 1).is_err());
+    assert!(coin.scale_to(Uint128::new(1), 0, 39).is_err());
+  }
+}
@@ -0,0 +1,67 @@
+//! `arbitrary::Arbitrary` for fuzzing, generating valid denoms and bech32 addresses only so fuzzers
+//! spend their time on realistic inputs.
+
+use arbitrary::{Arbitrary, Result, Unstructured};
+use bech32::{ToBase32, Variant};
+use cosmwasm_std::Addr;
+use crate::Fungible;
+
+const DENOM_FIRST: &[u8] = b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ";
+const DENOM_REST: &[u8] = b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789/:._-";
+const HRPS: &[&str] = &["cosmos", "juno", "osmo", "terra"];
+
+impl<'a> Arbitrary<'a> for Fungible {
+  fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+    if u.arbitrary()? {
+      let len = u.int_in_range(3..=128)?;
+      let mut denom = String::with_capacity(len);
+      denom.push(*u.choose(DENOM_FIRST)? as char);
+      for _ in 1..len {
+        denom.push(*u.choose(DENOM_REST)? as char);
+      }
+      Ok(Fungible::Coin(denom))
+    }
+    else {
+      // account and contract address lengths
+      let data: Vec<u8> = if u.arbitrary()? {
+        u.bytes(20)?.to_vec()
+      }
+      else {
+        u.bytes(32)?.to_vec()
+      };
+      let address = bech32::encode(u.choose(HRPS)?, data.to_base32(), Variant::Bech32)
+        .expect("valid hrp");
+      Ok(Fungible::Token(Addr::unchecked(address)))
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::validate_denom;
+  
+  #[test]
+  fn test_arbitrary() {
+    let seed: Vec<u8> = (0..4096u32).map(|i| (i.wrapping_mul(2654435761) >> 13) as u8).collect();
+    let mut u = Unstructured::new(&seed);
+    let (mut coins, mut tokens) = (0, 0);
+    
+    while let Ok(fungible) = Fungible::arbitrary(&mut u) {
+      match &fungible {
+        Fungible::Coin(denom) => {
+          coins += 1;
+          assert!(validate_denom(denom).is_ok(), "{}", denom);
+        },
+        Fungible::Token(_) => {
+          tokens += 1;
+          assert!(fungible.token_hrp().is_some(), "{}", fungible);
+        },
+      }
+      if u.is_empty() {
+        break;
+      }
+    }
+    assert!(coins > 0 && tokens > 0);
+  }
+}
@@ -0,0 +1,614 @@
+use std::{cmp::Ordering, collections::BTreeMap, fmt::Display, ops::{Add, AddAssign}, str::FromStr};
+use cosmwasm_std::{Coin, StdError, StdResult, Uint128, Uint256};
+use schemars::{gen::SchemaGenerator, schema::{InstanceType, ObjectValidation, Schema, SchemaObject, StringValidation}, JsonSchema};
+use serde::{Serialize, Deserialize};
+use crate::{validate_denom, Fungible, FungibleError};
+
+/// An amount of a `Fungible`.
+/// 
+/// Prefer `Asset::try_new`, which rejects zero amounts. Building the struct directly remains
+/// possible where zero is legitimate, e.g. for empty balances.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Asset {
+  pub info: Fungible,
+  pub amount: Uint128,
+}
+
+impl Asset {
+  /// Errors with `FungibleError::ZeroAmount` if `amount` is zero, which is almost always a logic
+  /// error leading to no-op messages.
+  pub fn try_new(info: Fungible, amount: Uint128) -> Result<Asset, FungibleError> {
+    if amount.is_zero() {
+      return Err(FungibleError::ZeroAmount { info: info.to_string() });
+    }
+    Ok(Asset { info, amount })
+  }
+  
+  /// Whether both assets refer to the same `Fungible`, regardless of their amounts.
+  pub fn is_same_asset(&self, other: &Asset) -> bool {
+    self.info == other.info
+  }
+  
+  /// Sum of two assets of the same `info`. Errors for different `info`s and on overflow.
+  pub fn checked_add(&self, other: &Asset) -> StdResult<Asset> {
+    if !self.is_same_asset(other) {
+      return Err(StdError::generic_err(format!("Cannot add {} and {}", self.info, other.info)));
+    }
+    Ok(Asset {
+      info: self.info.clone(),
+      amount: self.amount.checked_add(other.amount)?,
+    })
+  }
+  
+  /// Ordering of the amounts of two assets of the same `info`. Errors for different `info`s, whose
+  /// amounts are not comparable.
+  pub fn try_cmp_amounts(&self, other: &Asset) -> StdResult<Ordering> {
+    if !self.is_same_asset(other) {
+      return Err(StdError::generic_err(format!("Cannot compare amounts of {} and {}", self.info, other.info)));
+    }
+    Ok(self.amount.cmp(&other.amount))
+  }
+  
+  /// Splits the amount into `n` shares summing up to it exactly. The remainder of the division is
+  /// distributed one by one to the first shares. Errors if `n` is zero or exceeds the amount, so
+  /// every share is non-zero and at most `amount` shares are ever allocated.
+  /// 
+  /// The amount split is always `self.amount`; there is no separate `total` parameter, as it could
+  /// only ever disagree with the asset it is called on.
+  pub fn split_equal(&self, n: u32) -> StdResult<Vec<Uint128>> {
+    if n == 0 {
+      return Err(StdError::generic_err(format!("Cannot split {} into zero shares", self)));
+    }
+    if Uint128::from(n) > self.amount {
+      return Err(StdError::generic_err(format!("Cannot split {} into {} shares", self, n)));
+    }
+    
+    let n = Uint128::from(n);
+    let share = self.amount / n;
+    let remainder = (self.amount % n).u128();
+    Ok((0..n.u128())
+      .map(|i| if i < remainder { share + Uint128::one() } else { share })
+      .collect())
+  }
+  
+  /// Converts attached native funds into assets, merging duplicate denoms. The result is sorted.
+  /// Errors on zero amounts and overflow.
+  pub fn from_funds(funds: &[Coin]) -> StdResult<Vec<Asset>> {
+    let mut merged = BTreeMap::<&str, Uint128>::new();
+    for coin in funds {
+      if coin.amount.is_zero() {
+        return Err(StdError::generic_err(format!("Zero amount of {}", coin.denom)));
+      }
+      
+      let amount = merged.entry(&coin.denom).or_default();
+      *amount = amount.checked_add(coin.amount)?;
+    }
+    
+    // coins sort by denom, just like the BTreeMap keys
+    Ok(merged.into_iter()
+      .map(|(denom, amount)| Asset {
+        info: Fungible::Coin(denom.to_string()),
+        amount,
+      })
+      .collect())
+  }
+}
+
+/// Constrains `amount` to the decimal string `Uint128` serializes to, and references the schema of
+/// `Fungible` for `info`.
+impl JsonSchema for Asset {
+  fn schema_name() -> String {
+    "Asset".to_string()
+  }
+  
+  fn json_schema(gen: &mut SchemaGenerator) -> Schema {
+    let amount = SchemaObject {
+      instance_type: Some(InstanceType::String.into()),
+      string: Some(Box::new(StringValidation {
+        pattern: Some("^[0-9]+$".to_string()),
+        ..Default::default()
+      })),
+      ..Default::default()
+    };
+    
+    SchemaObject {
+      instance_type: Some(InstanceType::Object.into()),
+      object: Some(Box::new(ObjectValidation {
+        properties: [
+          ("info".to_string(), gen.subschema_for::<Fungible>()),
+          ("amount".to_string(), amount.into()),
+        ].into_iter().collect(),
+        required: ["info".to_string(), "amount".to_string()].into_iter().collect(),
+        ..Default::default()
+      })),
+      ..Default::default()
+    }.into()
+  }
+}
+
+/// A 256-bit amount of a `Fungible`, for intermediate results of high-precision math which may
+/// overflow an `Asset`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct Asset256 {
+  pub info: Fungible,
+  pub amount: Uint256,
+}
+
+impl Asset256 {
+  /// Sum of two assets of the same `info`. Errors for different `info`s and on overflow.
+  pub fn checked_add(&self, other: &Asset256) -> StdResult<Asset256> {
+    if self.info != other.info {
+      return Err(StdError::generic_err(format!("Cannot add {} and {}", self.info, other.info)));
+    }
+    Ok(Asset256 {
+      info: self.info.clone(),
+      amount: self.amount.checked_add(other.amount)?,
+    })
+  }
+  
+  /// Difference of two assets of the same `info`. Errors for different `info`s and on underflow.
+  pub fn checked_sub(&self, other: &Asset256) -> StdResult<Asset256> {
+    if self.info != other.info {
+      return Err(StdError::generic_err(format!("Cannot subtract {} from {}", other.info, self.info)));
+    }
+    Ok(Asset256 {
+      info: self.info.clone(),
+      amount: self.amount.checked_sub(other.amount)?,
+    })
+  }
+  
+  /// Multiplies the amount by `numerator / denominator`, rounding down.
+  pub fn checked_multiply_ratio(&self, numerator: impl Into<Uint256>, denominator: impl Into<Uint256>) -> StdResult<Asset256> {
+    Ok(Asset256 {
+      info: self.info.clone(),
+      amount: self.amount.checked_multiply_ratio(numerator, denominator)
+        .map_err(|err| StdError::generic_err(err.to_string()))?,
+    })
+  }
+  
+  /// Narrows into an `Asset`, erroring if the amount does not fit a `Uint128`.
+  pub fn try_into_asset(self) -> StdResult<Asset> {
+    Ok(Asset {
+      amount: self.amount.try_into()?,
+      info: self.info,
+    })
+  }
+}
+
+impl From<Asset> for Asset256 {
+  fn from(asset: Asset) -> Self {
+    Asset256 {
+      info: asset.info,
+      amount: asset.amount.into(),
+    }
+  }
+}
+
+/// Parses the concatenated form of the Cosmos SDK, e.g. `100uluna`. As denoms start with a letter,
+/// the amount is the leading run of digits. The denom is validated.
+pub fn parse_sdk_coin(s: &str) -> StdResult<Asset> {
+  let split = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+  let (amount, denom) = s.split_at(split);
+  if amount.is_empty() || denom.is_empty() {
+    return Err(StdError::generic_err(format!("Invalid coin: {}", s)));
+  }
+  
+  validate_denom(denom)?;
+  Ok(Asset {
+    info: Fungible::Coin(denom.to_string()),
+    amount: Uint128::from_str(amount)?,
+  })
+}
+
+/// A `Fungible` with a priority `weight`, for processing assets in a `BinaryHeap`.
+/// 
+/// Ordered such that a heap pops in descending `weight`, with ties broken deterministically by
+/// popping in ascending `Fungible` order.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WeightedFungible {
+  pub info: Fungible,
+  pub weight: Uint128,
+}
+
+impl PartialOrd for WeightedFungible {
+  fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+    Some(self.cmp(other))
+  }
+}
+
+impl Ord for WeightedFungible {
+  fn cmp(&self, other: &Self) -> Ordering {
+    self.weight.cmp(&other.weight).then_with(|| other.info.cmp(&self.info))
+  }
+}
+
+/// Subtracts `asset` from the remaining `budget` of its `info`, removing the entry once exhausted,
+/// and returns the remainder. Errors if the budget is insufficient.
+pub fn deduct(budget: &mut BTreeMap<Fungible, Uint128>, asset: &Asset) -> StdResult<Uint128> {
+  let available = budget.get(&asset.info).copied().unwrap_or_default();
+  let remaining = available.checked_sub(asset.amount)
+    .map_err(|_| StdError::generic_err(format!("Insufficient budget of {}: {} < {}", asset.info, available, asset.amount)))?;
+  
+  if remaining.is_zero() {
+    budget.remove(&asset.info);
+  }
+  else {
+    budget.insert(asset.info.clone(), remaining);
+  }
+  Ok(remaining)
+}
+
+/// Splits assets into `(natives, tokens)`, each sorted, e.g. to batch the natives into a single
+/// `BankMsg::Send` while executing each token separately.
+pub fn partition(assets: Vec<Asset>) -> (Vec<Asset>, Vec<Asset>) {
+  let (mut natives, mut tokens): (Vec<_>, Vec<_>) = assets.into_iter()
+    .partition(|asset| matches!(asset.info, Fungible::Coin(_)));
+  natives.sort();
+  tokens.sort();
+  (natives, tokens)
+}
+
+impl PartialOrd for Asset {
+  fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+    Some(self.cmp(other))
+  }
+}
+
+/// Orders by `info` first, then by `amount`.
+impl Ord for Asset {
+  fn cmp(&self, other: &Self) -> Ordering {
+    self.info.cmp(&other.info).then_with(|| self.amount.cmp(&other.amount))
+  }
+}
+
+/// Sums two assets of the same `info`, for trusted contexts such as tests.
+/// 
+/// # Panics
+/// 
+/// For different `info`s and on overflow. Use `Asset::checked_add` where these are possible.
+impl Add for Asset {
+  type Output = Asset;
+  
+  fn add(self, other: Asset) -> Asset {
+    self.checked_add(&other).unwrap_or_else(|err| panic!("{}", err))
+  }
+}
+
+/// See `Add`, including its panics.
+impl AddAssign for Asset {
+  fn add_assign(&mut self, other: Asset) {
+    *self = self.checked_add(&other).unwrap_or_else(|err| panic!("{}", err));
+  }
+}
+
+/// Renders e.g. `100 Coin(uluna)`, the amount as the plain canonical integer of `Uint128` without
+/// any separators or decimals, so it is always parseable by `FromStr`.
+impl Display for Asset {
+  fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+    write!(f, "{} {}", self.amount, self.info)
+  }
+}
+
+impl FromStr for Asset {
+  type Err = String;
+  
+  /// Parses the `Display` form, e.g. `100 Coin(uluna)`, separated by exactly one space.
+  fn from_str(s: &str) -> crate::Result<Self> {
+    let (amount, info) = s.split_once(' ').ok_or_else(|| format!("Invalid asset: {}", s))?;
+    // `Uint128::from_str` tolerates a leading `+`
+    if !amount.starts_with(|c: char| c.is_ascii_digit()) {
+      return Err(format!("Invalid asset: {}", s));
+    }
+    Ok(Asset {
+      info: Fungible::from_str(info)?,
+      amount: Uint128::from_str(amount).map_err(|err| err.to_string())?,
+    })
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use cosmwasm_std::{coin, Addr};
+  
+  fn asset(denom: &str, amount: u128) -> Asset {
+    Asset {
+      info: Fungible::Coin(denom.to_string()),
+      amount: Uint128::new(amount),
+    }
+  }
+  
+  #[test]
+  fn test_ordering() {
+    let token = Asset { info: Fungible::Token(Addr::unchecked("token")), amount: Uint128::new(500) };
+    let mut assets = vec![
+      asset("uusd", 100),
+      asset("uluna", 200),
+      token.clone(),
+      asset("uluna", 100),
+    ];
+    assets.sort();
+    
+    assert_eq!(assets, vec![token, asset("uluna", 100), asset("uluna", 200), asset("uusd", 100)]);
+  }
+  
+  #[test]
+  fn test_partition() {
+    let token = |addr: &str, amount: u128| Asset {
+      info: Fungible::Token(Addr::unchecked(addr)),
+      amount: Uint128::new(amount),
+    };
+    
+    let (natives, tokens) = partition(vec![
+      token("token2", 100),
+      asset("uusd", 100),
+      token("token1", 200),
+      asset("uluna", 300),
+      asset("uluna", 200),
+    ]);
+    assert_eq!(natives, vec![asset("uluna", 200), asset("uluna", 300), asset("uusd", 100)]);
+    assert_eq!(tokens, vec![token("token1", 200), token("token2", 100)]);
+    
+    assert_eq!(partition(vec![]), (vec![], vec![]));
+  }
+  
+  #[test]
+  fn test_asset256() {
+    let luna = Asset256::from(asset("uluna", u128::MAX));
+    
+    let squared = luna.checked_multiply_ratio(Uint128::MAX, 1u128).unwrap();
+    assert_eq!(squared.amount, Uint256::from(u128::MAX) * Uint256::from(u128::MAX));
+    assert_eq!(squared.info, luna.info);
+    
+    let one = Asset256::from(asset("uluna", 1));
+    let sum = luna.checked_add(&one).unwrap();
+    assert_eq!(sum.amount, Uint256::from(u128::MAX) + Uint256::from(1u128));
+    assert_eq!(sum.checked_sub(&one).unwrap(), luna);
+    
+    assert!(one.checked_sub(&luna).is_err());
+    assert!(squared.checked_multiply_ratio(Uint256::MAX, 1u128).is_err());
+    assert!(luna.checked_multiply_ratio(1u128, 0u128).is_err());
+  }
+  
+  #[test]
+  fn test_asset256_narrowing() {
+    let luna = Asset256::from(asset("uluna", u128::MAX));
+    assert_eq!(luna.clone().try_into_asset().unwrap(), asset("uluna", u128::MAX));
+    assert!(luna.checked_add(&Asset256::from(asset("uluna", 1))).unwrap().try_into_asset().is_err());
+  }
+  
+  #[test]
+  fn test_asset256_mismatch() {
+    let luna = Asset256::from(asset("uluna", 100));
+    let usd = Asset256::from(asset("uusd", 1));
+    
+    assert_eq!(luna.checked_add(&usd).unwrap_err(), StdError::generic_err("Cannot add Coin(uluna) and Coin(uusd)"));
+    assert_eq!(luna.checked_sub(&usd).unwrap_err(), StdError::generic_err("Cannot subtract Coin(uusd) from Coin(uluna)"));
+  }
+  
+  #[test]
+  fn test_weighted_heap() {
+    use std::collections::BinaryHeap;
+    
+    let weighted = |info: Fungible, weight: u128| WeightedFungible { info, weight: Uint128::new(weight) };
+    let luna = Fungible::Coin("uluna".to_string());
+    let usd = Fungible::Coin("uusd".to_string());
+    let token = Fungible::Token(Addr::unchecked("token"));
+    
+    let mut heap = BinaryHeap::from(vec![
+      weighted(usd.clone(), 100),
+      weighted(luna.clone(), 500),
+      weighted(token.clone(), 100),
+      weighted(luna.clone(), 100),
+      weighted(usd.clone(), 0),
+    ]);
+    
+    let mut popped = vec![];
+    while let Some(item) = heap.pop() {
+      popped.push(item);
+    }
+    assert_eq!(popped, vec![
+      weighted(luna.clone(), 500),
+      weighted(token, 100),
+      weighted(luna, 100),
+      weighted(usd.clone(), 100),
+      weighted(usd, 0),
+    ]);
+  }
+  
+  #[test]
+  fn test_add() {
+    assert_eq!(asset("uluna", 100) + asset("uluna", 50), asset("uluna", 150));
+    
+    let mut total = asset("uluna", 0);
+    total += asset("uluna", 100);
+    total += asset("uluna", 200);
+    assert_eq!(total, asset("uluna", 300));
+    
+    assert_eq!(asset("uluna", 100).checked_add(&asset("uluna", 1)).unwrap(), asset("uluna", 101));
+    assert!(asset("uluna", u128::MAX).checked_add(&asset("uluna", 1)).is_err());
+    assert_eq!(
+      asset("uluna", 100).checked_add(&asset("uusd", 1)).unwrap_err(),
+      StdError::generic_err("Cannot add Coin(uluna) and Coin(uusd)"),
+    );
+  }
+  
+  #[test]
+  #[should_panic(expected = "Cannot add Coin(uluna) and Coin(uusd)")]
+  fn test_add_mismatched() {
+    let _ = asset("uluna", 100) + asset("uusd", 50);
+  }
+  
+  #[test]
+  fn test_try_cmp_amounts() {
+    assert_eq!(asset("uluna", 100).try_cmp_amounts(&asset("uluna", 50)).unwrap(), Ordering::Greater);
+    assert_eq!(asset("uluna", 50).try_cmp_amounts(&asset("uluna", 50)).unwrap(), Ordering::Equal);
+    assert_eq!(asset("uluna", 0).try_cmp_amounts(&asset("uluna", 50)).unwrap(), Ordering::Less);
+    
+    let token = Asset { info: Fungible::Token(Addr::unchecked("whDAI")), amount: Uint128::new(50) };
+    assert_eq!(
+      asset("uluna", 100).try_cmp_amounts(&token).unwrap_err(),
+      StdError::generic_err("Cannot compare amounts of Coin(uluna) and Token(whDAI)"),
+    );
+  }
+  
+  #[test]
+  fn test_split_equal() {
+    assert_eq!(asset("uluna", 300).split_equal(3).unwrap(), vec![Uint128::new(100); 3]);
+    
+    let shares = asset("uluna", 302).split_equal(3).unwrap();
+    assert_eq!(shares, vec![Uint128::new(101), Uint128::new(101), Uint128::new(100)]);
+    assert_eq!(shares.iter().sum::<Uint128>(), Uint128::new(302));
+    
+    let shares = asset("uluna", u128::MAX).split_equal(7).unwrap();
+    assert_eq!(shares.iter().sum::<Uint128>(), Uint128::MAX);
+    assert_eq!(asset("uluna", 3).split_equal(3).unwrap(), vec![Uint128::one(); 3]);
+    
+    assert_eq!(
+      asset("uluna", 300).split_equal(0).unwrap_err(),
+      StdError::generic_err("Cannot split 300 Coin(uluna) into zero shares"),
+    );
+    assert_eq!(
+      asset("uluna", 2).split_equal(3).unwrap_err(),
+      StdError::generic_err("Cannot split 2 Coin(uluna) into 3 shares"),
+    );
+    // rejected before allocating a single share
+    assert_eq!(
+      asset("uluna", 1).split_equal(u32::MAX).unwrap_err(),
+      StdError::generic_err(format!("Cannot split 1 Coin(uluna) into {} shares", u32::MAX)),
+    );
+    assert!(asset("uluna", 0).split_equal(1).is_err());
+  }
+  
+  #[test]
+  fn test_deduct() {
+    let luna = Fungible::Coin("uluna".to_string());
+    let mut budget = BTreeMap::from([(luna.clone(), Uint128::new(300))]);
+    
+    assert_eq!(deduct(&mut budget, &asset("uluna", 100)).unwrap(), Uint128::new(200));
+    assert_eq!(budget.get(&luna), Some(&Uint128::new(200)));
+    
+    assert_eq!(deduct(&mut budget, &asset("uluna", 200)).unwrap(), Uint128::zero());
+    assert!(budget.is_empty());
+  }
+  
+  #[test]
+  fn test_deduct_insufficient() {
+    let mut budget = BTreeMap::from([(Fungible::Coin("uluna".to_string()), Uint128::new(100))]);
+    
+    assert_eq!(
+      deduct(&mut budget, &asset("uluna", 101)).unwrap_err(),
+      StdError::generic_err("Insufficient budget of Coin(uluna): 100 < 101"),
+    );
+    assert_eq!(budget.values().collect::<Vec<_>>(), vec![&Uint128::new(100)]);
+    assert!(deduct(&mut budget, &asset("uusd", 1)).is_err());
+  }
+  
+  #[test]
+  fn test_try_new() {
+    let luna = Fungible::Coin("uluna".to_string());
+    assert_eq!(Asset::try_new(luna.clone(), Uint128::new(100)), Ok(asset("uluna", 100)));
+    assert_eq!(
+      Asset::try_new(luna, Uint128::zero()),
+      Err(FungibleError::ZeroAmount { info: "Coin(uluna)".to_string() }),
+    );
+  }
+  
+  #[test]
+  fn test_schema() {
+    let schema = schemars::schema_for!(Asset);
+    let object = schema.schema.object.unwrap();
+    
+    let amount = object.properties["amount"].clone().into_object();
+    assert_eq!(amount.instance_type, Some(InstanceType::String.into()));
+    assert_eq!(amount.string.unwrap().pattern.as_deref(), Some("^[0-9]+$"));
+    
+    let info = object.properties["info"].clone().into_object();
+    assert_eq!(info.reference.as_deref(), Some("#/definitions/Fungible"));
+    assert!(schema.definitions.contains_key("Fungible"));
+    
+    assert_eq!(object.required.len(), 2);
+  }
+  
+  #[test]
+  fn test_is_same_asset() {
+    assert!(asset("uluna", 100).is_same_asset(&asset("uluna", 200)));
+    assert!(!asset("uluna", 100).is_same_asset(&asset("uusd", 100)));
+  }
+  
+  #[test]
+  fn test_from_funds() {
+    let assets = Asset::from_funds(&[coin(100, "uusd"), coin(200, "uluna")]).unwrap();
+    assert_eq!(assets, vec![asset("uluna", 200), asset("uusd", 100)]);
+  }
+  
+  #[test]
+  fn test_from_funds_merge() {
+    let assets = Asset::from_funds(&[coin(100, "uluna"), coin(300, "uusd"), coin(200, "uluna")]).unwrap();
+    assert_eq!(assets, vec![asset("uluna", 300), asset("uusd", 300)]);
+    
+    assert!(Asset::from_funds(&[coin(u128::MAX, "uluna"), coin(1, "uluna")]).is_err());
+  }
+  
+  #[test]
+  fn test_from_funds_zero() {
+    assert_eq!(
+      Asset::from_funds(&[coin(100, "uluna"), coin(0, "uusd")]).unwrap_err(),
+      StdError::generic_err("Zero amount of uusd"),
+    );
+  }
+  
+  #[test]
+  fn test_stringify() {
+    let coin = asset("uluna", 100);
+    let token = Asset {
+      info: Fungible::Token(Addr::unchecked("whDAI")),
+      amount: Uint128::new(200),
+    };
+    
+    assert_eq!(coin.to_string(), "100 Coin(uluna)");
+    assert_eq!(token.to_string(), "200 Token(whDAI)");
+    assert_eq!(Asset::from_str(&coin.to_string()).unwrap(), coin);
+    assert_eq!(Asset::from_str(&token.to_string()).unwrap(), token);
+  }
+  
+  #[test]
+  fn test_parse_sdk_coin() {
+    assert_eq!(parse_sdk_coin("100uluna").unwrap(), asset("uluna", 100));
+    assert_eq!(parse_sdk_coin("0uatom").unwrap(), asset("uatom", 0));
+    assert_eq!(parse_sdk_coin("5ibc/27394FB:1").unwrap(), asset("ibc/27394FB:1", 5));
+  }
+  
+  #[test]
+  fn test_parse_sdk_coin_invalid() {
+    assert_eq!(parse_sdk_coin("uluna").unwrap_err(), StdError::generic_err("Invalid coin: uluna"));
+    assert_eq!(parse_sdk_coin("100").unwrap_err(), StdError::generic_err("Invalid coin: 100"));
+    assert!(parse_sdk_coin("").is_err());
+    assert!(parse_sdk_coin("100 uluna").is_err());
+    assert!(parse_sdk_coin("100u").is_err());
+    assert!(parse_sdk_coin("-100uluna").is_err());
+  }
+  
+  #[test]
+  fn test_stringify_large() {
+    for amount in [u128::MAX, u128::MAX - 1, 1_000_000_000_000_000_000] {
+      let large = asset("uluna", amount);
+      assert_eq!(large.to_string(), format!("{} Coin(uluna)", amount));
+      assert_eq!(Asset::from_str(&large.to_string()).unwrap(), large);
+    }
+    assert_eq!(asset("uluna", u128::MAX).to_string(), "340282366920938463463374607431768211455 Coin(uluna)");
+    
+    assert!(Asset::from_str("1,000 Coin(uluna)").is_err());
+    assert!(Asset::from_str("1.5 Coin(uluna)").is_err());
+  }
+  
+  #[test]
+  fn test_parse_invalid() {
+    assert!(Asset::from_str("100Coin(uluna)").is_err());
+    assert!(Asset::from_str("100  Coin(uluna)").is_err());
+    assert!(Asset::from_str(" 100 Coin(uluna)").is_err());
+    assert!(Asset::from_str("abc Coin(uluna)").is_err());
+    assert_eq!(Asset::from_str("+100 Coin(uluna)").unwrap_err(), "Invalid asset: +100 Coin(uluna)");
+    assert!(Asset::from_str(" Coin(uluna)").is_err());
+  }
+}
@@ -0,0 +1,109 @@
+use cosmwasm_std::{StdResult, Uint128};
+use crate::{Asset, Fungible};
+
+/// A list of assets with at most one entry per `Fungible`, kept sorted by `Fungible`'s `Ord`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct AssetList(Vec<Asset>);
+
+impl AssetList {
+  pub fn new() -> Self {
+    Self::default()
+  }
+  
+  /// Adds `asset`, merging it into the existing entry of the same `Fungible`, if any. Errors on
+  /// overflow, in which case the list is left unchanged.
+  pub fn add(&mut self, asset: Asset) -> StdResult<()> {
+    match self.0.binary_search_by(|entry| entry.info.cmp(&asset.info)) {
+      Ok(index) => {
+        let entry = &mut self.0[index];
+        entry.amount = entry.amount.checked_add(asset.amount)?;
+      },
+      Err(index) => self.0.insert(index, asset),
+    }
+    Ok(())
+  }
+  
+  /// Fallible counterpart of `collect`.
+  pub fn try_from_iter<I: IntoIterator<Item = Asset>>(iter: I) -> StdResult<Self> {
+    let mut list = Self::new();
+    for asset in iter {
+      list.add(asset)?;
+    }
+    Ok(list)
+  }
+  
+  pub fn iter(&self) -> std::slice::Iter<'_, Asset> {
+    self.0.iter()
+  }
+  
+  pub fn find(&self, info: &Fungible) -> Option<&Asset> {
+    self.0.binary_search_by(|entry| entry.info.cmp(info))
+      .ok()
+      .map(|index| &self.0[index])
+  }
+  
+  /// Total amount of `info` in this list, zero if absent.
+  pub fn total_of(&self, info: &Fungible) -> Uint128 {
+    self.find(info).map(|asset| asset.amount).unwrap_or_default()
+  }
+  
+  pub fn into_vec(self) -> Vec<Asset> {
+    self.0
+  }
+}
+
+/// Merges duplicates like `AssetList::add`.
+/// 
+/// # Panics
+/// 
+/// Panics on overflow. Use `AssetList::try_from_iter` where amounts are untrusted.
+impl FromIterator<Asset> for AssetList {
+  fn from_iter<I: IntoIterator<Item = Asset>>(iter: I) -> Self {
+    Self::try_from_iter(iter).expect("AssetList amount overflow")
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use cosmwasm_std::Addr;
+  
+  fn asset(info: &Fungible, amount: u128) -> Asset {
+    Asset {
+      info: info.clone(),
+      amount: Uint128::new(amount),
+    }
+  }
+  
+  #[test]
+  fn test_collect() {
+    let luna = Fungible::Coin("uluna".to_string());
+    let usd = Fungible::Coin("uusd".to_string());
+    let token = Fungible::Token(Addr::unchecked("token"));
+    
+    let list: AssetList = vec![
+      asset(&usd, 100),
+      asset(&luna, 200),
+      asset(&token, 300),
+      asset(&usd, 400),
+    ].into_iter().collect();
+    
+    assert_eq!(list.iter().cloned().collect::<Vec<_>>(), vec![asset(&token, 300), asset(&luna, 200), asset(&usd, 500)]);
+    assert_eq!(list.total_of(&usd), Uint128::new(500));
+    assert_eq!(list.total_of(&Fungible::Coin("ukrw".to_string())), Uint128::zero());
+    assert_eq!(list.find(&token), Some(&asset(&token, 300)));
+  }
+  
+  #[test]
+  fn test_overflow() {
+    let luna = Fungible::Coin("uluna".to_string());
+    assert!(AssetList::try_from_iter(vec![asset(&luna, u128::MAX), asset(&luna, 1)]).is_err());
+  }
+  
+  #[test]
+  #[should_panic(expected = "AssetList amount overflow")]
+  fn test_collect_overflow() {
+    let luna = Fungible::Coin("uluna".to_string());
+    let _: AssetList = vec![asset(&luna, u128::MAX), asset(&luna, 1)].into_iter().collect();
+  }
+}
@@ -0,0 +1,83 @@
+use std::collections::BTreeMap;
+use cosmwasm_std::{Addr, QuerierWrapper, StdResult, Uint128};
+use crate::{query_balances, Fungible};
+
+/// Snapshot of the balances of an account, e.g. to detect fee-on-transfer tokens by comparing the
+/// balances before and after an operation.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Balances(pub BTreeMap<Fungible, Uint128>);
+
+impl Balances {
+  /// Queries the current balances of `account` for all `assets`.
+  pub fn snapshot(querier: &QuerierWrapper, account: &Addr, assets: &[Fungible]) -> StdResult<Balances> {
+    let balances = query_balances(querier, account, assets)?;
+    Ok(Balances(assets.iter().cloned().zip(balances).collect()))
+  }
+  
+  /// Signed change of every asset in either snapshot from `self` to `later`, an asset missing from
+  /// a snapshot counting as zero. Deltas saturate at the bounds of `i128`.
+  pub fn diff(&self, later: &Balances) -> BTreeMap<Fungible, i128> {
+    self.0.keys()
+      .chain(later.0.keys())
+      .map(|asset| {
+        let before = self.0.get(asset).copied().unwrap_or_default().u128();
+        let after = later.0.get(asset).copied().unwrap_or_default().u128();
+        let delta = if after >= before {
+          i128::try_from(after - before).unwrap_or(i128::MAX)
+        }
+        else {
+          i128::try_from(before - after).map(|delta| -delta).unwrap_or(i128::MIN)
+        };
+        (asset.clone(), delta)
+      })
+      .collect()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use cosmwasm_std::coin;
+  use cosmwasm_std::testing::MockQuerier;
+  
+  #[test]
+  fn test_snapshot() {
+    let querier: MockQuerier = MockQuerier::new(&[("alice", &[coin(100, "uluna")])]);
+    let querier = QuerierWrapper::<cosmwasm_std::Empty>::new(&querier);
+    let luna = Fungible::Coin("uluna".to_string());
+    let usd = Fungible::Coin("uusd".to_string());
+    
+    let snapshot = Balances::snapshot(&querier, &Addr::unchecked("alice"), &[luna.clone(), usd.clone()]).unwrap();
+    assert_eq!(snapshot, Balances(BTreeMap::from([(luna, Uint128::new(100)), (usd, Uint128::zero())])));
+  }
+  
+  #[test]
+  fn test_diff() {
+    let luna = Fungible::Coin("uluna".to_string());
+    let usd = Fungible::Coin("uusd".to_string());
+    let token = Fungible::Token(Addr::unchecked("token"));
+    let fee = Fungible::Token(Addr::unchecked("fee"));
+    
+    let before = Balances(BTreeMap::from([
+      (luna.clone(), Uint128::new(100)),
+      (usd.clone(), Uint128::new(200)),
+      (token.clone(), Uint128::new(300)),
+    ]));
+    let after = Balances(BTreeMap::from([
+      (luna.clone(), Uint128::new(150)),
+      (usd.clone(), Uint128::new(200)),
+      (fee.clone(), Uint128::new(5)),
+    ]));
+    
+    assert_eq!(before.diff(&after), BTreeMap::from([
+      (luna, 50),
+      (usd, 0),
+      (token.clone(), -300),
+      (fee, 5),
+    ]));
+    
+    let huge = Balances(BTreeMap::from([(token.clone(), Uint128::MAX)]));
+    assert_eq!(Balances::default().diff(&huge)[&token], i128::MAX);
+    assert_eq!(huge.diff(&Balances::default())[&token], i128::MIN);
+  }
+}
@@ -0,0 +1,155 @@
+use std::collections::HashMap;
+use cosmwasm_std::{QuerierWrapper, StdError, StdResult, Storage};
+use cw20::{Cw20QueryMsg, TokenInfoResponse};
+use cw_storage_plus::Map;
+use crate::Fungible;
+
+/// Memoizes CW20 `TokenInfo` lookups for the duration of a call, so tokens touched repeatedly are
+/// only queried once.
+pub struct FungibleCache<'a> {
+  querier: &'a QuerierWrapper<'a>,
+  token_infos: HashMap<Fungible, TokenInfoResponse>,
+}
+
+impl<'a> FungibleCache<'a> {
+  pub fn new(querier: &'a QuerierWrapper<'a>) -> Self {
+    FungibleCache {
+      querier,
+      token_infos: HashMap::new(),
+    }
+  }
+  
+  /// Token info of a CW20, queried on first access. `None` for coins, without querying.
+  pub fn token_info(&mut self, asset: &Fungible) -> StdResult<Option<&TokenInfoResponse>> {
+    let token = match asset {
+      Fungible::Coin(_) => return Ok(None),
+      Fungible::Token(token) => token,
+    };
+    
+    if !self.token_infos.contains_key(asset) {
+      let info: TokenInfoResponse = self.querier.query_wasm_smart(token, &Cw20QueryMsg::TokenInfo {})?;
+      self.token_infos.insert(asset.clone(), info);
+    }
+    Ok(self.token_infos.get(asset))
+  }
+  
+  /// Decimals of a CW20, queried on first access. `None` for coins, without querying.
+  pub fn decimals(&mut self, asset: &Fungible) -> StdResult<Option<u8>> {
+    Ok(self.token_info(asset)?.map(|info| info.decimals))
+  }
+}
+
+/// Decimals of `key` as persisted in `map`, querying and storing the `TokenInfo` of a token on
+/// first access. Coins cannot be queried for their decimals, so `native_default` is returned for
+/// coins missing from `map`, erroring if `None`.
+pub fn cache_decimals(
+  map: &Map<Fungible, u8>,
+  store: &mut dyn Storage,
+  key: &Fungible,
+  querier: &QuerierWrapper,
+  native_default: Option<u8>,
+) -> StdResult<u8> {
+  if let Some(decimals) = map.may_load(store, key.clone())? {
+    return Ok(decimals);
+  }
+  
+  match key {
+    Fungible::Coin(_) => native_default.ok_or_else(|| StdError::generic_err(format!("Unknown decimals of {}", key))),
+    Fungible::Token(token) => {
+      let info: TokenInfoResponse = querier.query_wasm_smart(token, &Cw20QueryMsg::TokenInfo {})?;
+      map.save(store, key.clone(), &info.decimals)?;
+      Ok(info.decimals)
+    },
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::{cell::Cell, rc::Rc};
+  use cosmwasm_std::{to_json_binary, Addr, ContractResult, Empty, SystemResult, Uint128};
+  use cosmwasm_std::testing::{MockQuerier, MockStorage};
+  
+  fn counting_querier(count: Rc<Cell<u32>>) -> MockQuerier {
+    let mut querier = MockQuerier::new(&[]);
+    querier.update_wasm(move |_| {
+      count.set(count.get() + 1);
+      SystemResult::Ok(ContractResult::Ok(to_json_binary(&TokenInfoResponse {
+        name: "Test Token".to_string(),
+        symbol: "TEST".to_string(),
+        decimals: 8,
+        total_supply: Uint128::new(1000),
+      }).unwrap()))
+    });
+    querier
+  }
+  
+  #[test]
+  fn test_memoized() {
+    let count = Rc::new(Cell::new(0));
+    let querier = counting_querier(count.clone());
+    let querier = QuerierWrapper::<Empty>::new(&querier);
+    let mut cache = FungibleCache::new(&querier);
+    let token = Fungible::Token(Addr::unchecked("token"));
+    
+    assert_eq!(cache.decimals(&token).unwrap(), Some(8));
+    assert_eq!(count.get(), 1);
+    assert_eq!(cache.token_info(&token).unwrap().unwrap().symbol, "TEST");
+    assert_eq!(cache.decimals(&token).unwrap(), Some(8));
+    assert_eq!(count.get(), 1);
+    
+    cache.decimals(&Fungible::Token(Addr::unchecked("other"))).unwrap();
+    assert_eq!(count.get(), 2);
+  }
+  
+  #[test]
+  fn test_native() {
+    let count = Rc::new(Cell::new(0));
+    let querier = counting_querier(count.clone());
+    let querier = QuerierWrapper::<Empty>::new(&querier);
+    let mut cache = FungibleCache::new(&querier);
+    
+    assert_eq!(cache.decimals(&Fungible::Coin("uluna".to_string())).unwrap(), None);
+    assert_eq!(count.get(), 0);
+  }
+  
+  const DECIMALS: Map<Fungible, u8> = Map::new("decimals");
+  
+  #[test]
+  fn test_cache_decimals() {
+    let count = Rc::new(Cell::new(0));
+    let querier = counting_querier(count.clone());
+    let querier = QuerierWrapper::<Empty>::new(&querier);
+    let mut store = MockStorage::new();
+    let token = Fungible::Token(Addr::unchecked("token"));
+    
+    // miss: queries and stores
+    assert_eq!(cache_decimals(&DECIMALS, &mut store, &token, &querier, None).unwrap(), 8);
+    assert_eq!(count.get(), 1);
+    assert_eq!(DECIMALS.load(&store, token.clone()).unwrap(), 8);
+    
+    // hit
+    DECIMALS.save(&mut store, token.clone(), &6).unwrap();
+    assert_eq!(cache_decimals(&DECIMALS, &mut store, &token, &querier, None).unwrap(), 6);
+    assert_eq!(count.get(), 1);
+  }
+  
+  #[test]
+  fn test_cache_decimals_native() {
+    let count = Rc::new(Cell::new(0));
+    let querier = counting_querier(count.clone());
+    let querier = QuerierWrapper::<Empty>::new(&querier);
+    let mut store = MockStorage::new();
+    let luna = Fungible::Coin("uluna".to_string());
+    
+    assert_eq!(
+      cache_decimals(&DECIMALS, &mut store, &luna, &querier, None).unwrap_err(),
+      StdError::generic_err("Unknown decimals of Coin(uluna)"),
+    );
+    assert_eq!(cache_decimals(&DECIMALS, &mut store, &luna, &querier, Some(6)).unwrap(), 6);
+    
+    DECIMALS.save(&mut store, luna.clone(), &8).unwrap();
+    assert_eq!(cache_decimals(&DECIMALS, &mut store, &luna, &querier, None).unwrap(), 8);
+    assert_eq!(count.get(), 0);
+  }
+}
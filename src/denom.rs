@@ -0,0 +1,227 @@
+use std::collections::{HashMap, HashSet};
+use cosmwasm_std::Coin;
+#[cfg(feature = "regex")]
+use regex::Regex;
+use crate::{Fungible, FungibleError};
+
+/// Validates a native denom against the Cosmos SDK rules, i.e. `[a-zA-Z][a-zA-Z0-9/:._-]{2,127}`.
+pub fn validate_denom(denom: &str) -> Result<(), FungibleError> {
+  let invalid = || FungibleError::InvalidDenom { denom: denom.to_string() };
+  
+  if denom.len() < 3 || denom.len() > 128 {
+    return Err(invalid());
+  }
+  
+  let mut chars = denom.chars();
+  if !chars.next().unwrap().is_ascii_alphabetic() {
+    return Err(invalid());
+  }
+  if !chars.all(|c| c.is_ascii_alphanumeric() || "/:._-".contains(c)) {
+    return Err(invalid());
+  }
+  Ok(())
+}
+
+/// Chain-specific rules for native denoms.
+#[cfg(feature = "regex")]
+#[derive(Clone, Debug)]
+pub struct DenomRules {
+  pub min_len: usize,
+  pub max_len: usize,
+  /// Pattern the whole denom must match, if any.
+  pub pattern: Option<Regex>,
+}
+
+#[cfg(feature = "regex")]
+impl DenomRules {
+  /// The Cosmos SDK rules, as also enforced by `validate_denom`.
+  pub fn cosmos_sdk_default() -> Self {
+    DenomRules {
+      min_len: 3,
+      max_len: 128,
+      pattern: Some(Regex::new(r"^[a-zA-Z][a-zA-Z0-9/:._-]*$").unwrap()),
+    }
+  }
+}
+
+impl Fungible {
+  /// Opt-in equality comparing coin denoms case-insensitively, for chains registering denoms that
+  /// way. Token addresses are compared exactly, as they are lowercase by spec. Plain `Eq` remains
+  /// case-sensitive.
+  pub fn eq_denom_ci(&self, other: &Fungible) -> bool {
+    match (self, other) {
+      (Fungible::Coin(a), Fungible::Coin(b)) => a.eq_ignore_ascii_case(b),
+      (Fungible::Token(a), Fungible::Token(b)) => a == b,
+      _ => false,
+    }
+  }
+}
+
+#[cfg(feature = "regex")]
+impl Fungible {
+  /// Validates the denom of a coin against `rules`. Tokens always pass.
+  pub fn validate_with(&self, rules: &DenomRules) -> Result<(), FungibleError> {
+    match self {
+      Fungible::Coin(denom) => {
+        let valid_len = denom.len() >= rules.min_len && denom.len() <= rules.max_len;
+        let valid_pattern = match &rules.pattern {
+          Some(pattern) => pattern.is_match(denom),
+          None => true,
+        };
+        if !valid_len || !valid_pattern {
+          return Err(FungibleError::InvalidDenom { denom: denom.clone() });
+        }
+        Ok(())
+      },
+      Fungible::Token(_) => Ok(()),
+    }
+  }
+}
+
+/// Spells an IBC voucher as `ibc/` followed by its uppercase hex hash, the form the SDK emits, as
+/// the hash is case-insensitive. Every other denom is case-sensitive and returned unchanged.
+fn normalize_denom(denom: &str) -> String {
+  match denom.get(..4).zip(denom.get(4..)) {
+    Some((prefix, hash)) if prefix.eq_ignore_ascii_case("ibc/") && !hash.is_empty() && hash.bytes().all(|b| b.is_ascii_hexdigit()) =>
+      format!("ibc/{}", hash.to_ascii_uppercase()),
+    _ => denom.to_string(),
+  }
+}
+
+/// Maps native denoms through `canonical` (e.g. IBC vouchers of the same asset via different paths
+/// to one canonical denom) and drops the resulting duplicates, keeping the first occurrence.
+/// Tokens pass through untouched.
+/// 
+/// IBC denoms are normalized to their uppercase hash before the lookup and the comparison, so
+/// `canonical` is keyed by that form and `ibc/abcd` collapses with `ibc/ABCD`.
+pub fn dedup_native(denoms: &[Fungible], canonical: &HashMap<String, String>) -> Vec<Fungible> {
+  let mut seen = HashSet::new();
+  denoms.iter()
+    .filter_map(|fungible| match fungible {
+      Fungible::Coin(denom) => {
+        let denom = normalize_denom(denom);
+        let denom = canonical.get(&denom).map_or(denom, |canonical| normalize_denom(canonical));
+        seen.insert(denom.clone()).then_some(Fungible::Coin(denom))
+      },
+      Fungible::Token(_) => Some(fungible.clone()),
+    })
+    .collect()
+}
+
+/// Strict conversion validating the denom. Build `Fungible::Coin` directly to skip validation.
+impl<'a> TryFrom<&'a Coin> for Fungible {
+  type Error = FungibleError;
+  
+  fn try_from(coin: &'a Coin) -> Result<Self, Self::Error> {
+    validate_denom(&coin.denom)?;
+    Ok(Fungible::Coin(coin.denom.clone()))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use cosmwasm_std::{coin, Addr};
+  
+  #[test]
+  fn test_eq_denom_ci() {
+    let lower = Fungible::Coin("uluna".to_string());
+    let mixed = Fungible::Coin("uLuna".to_string());
+    
+    assert!(lower.eq_denom_ci(&mixed));
+    assert_ne!(lower, mixed);
+    assert!(!lower.eq_denom_ci(&Fungible::Coin("uusd".to_string())));
+    
+    let token = Fungible::Token(Addr::unchecked("token"));
+    assert!(token.eq_denom_ci(&token));
+    assert!(!token.eq_denom_ci(&Fungible::Token(Addr::unchecked("TOKEN"))));
+    assert!(!Fungible::Coin("token".to_string()).eq_denom_ci(&token));
+  }
+  
+  #[test]
+  fn test_validate_denom() {
+    assert!(validate_denom("uluna").is_ok());
+    assert!(validate_denom("ibc/27394FB092D2ECCD56123C74F36E4C1F926001CEADA9CA97EA622B25F41E5EB2").is_ok());
+    assert!(validate_denom("factory/osmo1abc/foo.bar-baz_1:2").is_ok());
+    
+    assert!(validate_denom("").is_err());
+    assert!(validate_denom("ab").is_err());
+    assert!(validate_denom("1uluna").is_err());
+    assert!(validate_denom("u luna").is_err());
+    assert!(validate_denom(&"u".repeat(129)).is_err());
+  }
+  
+  #[test]
+  fn test_try_from_coin() {
+    assert_eq!(Fungible::try_from(&coin(100, "uluna")), Ok(Fungible::Coin("uluna".to_string())));
+    assert_eq!(
+      Fungible::try_from(&coin(100, "u$d")),
+      Err(FungibleError::InvalidDenom { denom: "u$d".to_string() }),
+    );
+  }
+  
+  #[cfg(feature = "regex")]
+  #[test]
+  fn test_validate_with() {
+    let sdk = DenomRules::cosmos_sdk_default();
+    let strict = DenomRules {
+      min_len: 4,
+      max_len: 8,
+      pattern: Some(Regex::new(r"^u[a-z]+$").unwrap()),
+    };
+    let coin = |denom: &str| Fungible::Coin(denom.to_string());
+    
+    assert!(coin("uluna").validate_with(&sdk).is_ok());
+    assert!(coin("ibc/ABCD").validate_with(&sdk).is_ok());
+    assert!(coin("1uluna").validate_with(&sdk).is_err());
+    
+    assert!(coin("uluna").validate_with(&strict).is_ok());
+    assert_eq!(coin("ibc/ABCD").validate_with(&strict), Err(FungibleError::InvalidDenom { denom: "ibc/ABCD".to_string() }));
+    assert!(coin("uat").validate_with(&strict).is_err());
+    assert!(coin("ulunaclassic").validate_with(&strict).is_err());
+    assert!(Fungible::Token(Addr::unchecked("token")).validate_with(&strict).is_ok());
+  }
+  
+  #[test]
+  fn test_dedup_native() {
+    let canonical = HashMap::from([
+      ("ibc/AAAA".to_string(), "ibc/ATOM".to_string()),
+      ("ibc/BBBB".to_string(), "ibc/ATOM".to_string()),
+    ]);
+    let denoms = vec![
+      Fungible::Coin("ibc/AAAA".to_string()),
+      Fungible::Token(Addr::unchecked("token")),
+      Fungible::Coin("uluna".to_string()),
+      Fungible::Coin("ibc/BBBB".to_string()),
+    ];
+    
+    assert_eq!(dedup_native(&denoms, &canonical), vec![
+      Fungible::Coin("ibc/ATOM".to_string()),
+      Fungible::Token(Addr::unchecked("token")),
+      Fungible::Coin("uluna".to_string()),
+    ]);
+  }
+  
+  #[test]
+  fn test_dedup_native_mixed_case_ibc() {
+    let canonical = HashMap::from([("ibc/AAAA".to_string(), "ibc/ATOM".to_string())]);
+    let denoms = vec![
+      Fungible::Coin("ibc/27394fb092d2eccd".to_string()),
+      Fungible::Coin("ibc/27394FB092D2ECCD".to_string()),
+      Fungible::Coin("ibc/aaaa".to_string()),
+      Fungible::Coin("IBC/BbBb".to_string()),
+      Fungible::Coin("ibc/BBBB".to_string()),
+      Fungible::Coin("uluna".to_string()),
+      Fungible::Coin("uLuna".to_string()),
+    ];
+    
+    // denoms other than IBC vouchers stay case-sensitive
+    assert_eq!(dedup_native(&denoms, &canonical), vec![
+      Fungible::Coin("ibc/27394FB092D2ECCD".to_string()),
+      Fungible::Coin("ibc/ATOM".to_string()),
+      Fungible::Coin("ibc/BBBB".to_string()),
+      Fungible::Coin("uluna".to_string()),
+      Fungible::Coin("uLuna".to_string()),
+    ]);
+  }
+}
@@ -0,0 +1,42 @@
+use cosmwasm_std::StdError;
+use thiserror::Error;
+use crate::FungibleKind;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum FungibleError {
+  #[error("Invalid denom: {denom}")]
+  InvalidDenom { denom: String },
+  
+  #[error("Invalid address: {address}")]
+  InvalidAddress { address: String },
+  
+  #[error("Empty {kind:?} identifier")]
+  EmptyIdentifier { kind: FungibleKind },
+  
+  #[error("{kind:?} identifier too long: {len} > {max} bytes")]
+  IdentifierTooLong { kind: FungibleKind, len: usize, max: usize },
+  
+  #[error("Zero amount of {info}")]
+  ZeroAmount { info: String },
+  
+  #[error("Not sorted at index {index}")]
+  NotSorted { index: usize },
+  
+  #[error("Expected {expected:?}, got {got:?}")]
+  WrongKind { expected: FungibleKind, got: FungibleKind },
+  
+  #[error("Unknown asset scheme: {input}")]
+  UnknownScheme { input: String },
+  
+  #[error("Unsupported asset: {asset}")]
+  UnsupportedAsset { asset: String },
+  
+  #[error("Suspicious classification: {fungible}")]
+  SuspiciousClassification { fungible: String },
+}
+
+impl From<FungibleError> for StdError {
+  fn from(err: FungibleError) -> Self {
+    StdError::generic_err(err.to_string())
+  }
+}
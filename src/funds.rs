@@ -0,0 +1,117 @@
+use std::collections::BTreeMap;
+use cosmwasm_std::{MessageInfo, StdError, StdResult, Uint128};
+use crate::{Asset, Fungible};
+
+/// Errors unless the attached native funds exactly match the native assets of `expected`, in any
+/// order. Token assets are not attached, hence no native funds may accompany them.
+pub fn assert_funds_exact(expected: &[Asset], info: &MessageInfo) -> StdResult<()> {
+  let mut attached = BTreeMap::<&str, Uint128>::new();
+  for coin in &info.funds {
+    let amount = attached.entry(&coin.denom).or_default();
+    *amount = amount.checked_add(coin.amount)?;
+  }
+  
+  let mut natives = BTreeMap::<&str, Uint128>::new();
+  for asset in expected {
+    if let Fungible::Coin(denom) = &asset.info {
+      let amount = natives.entry(denom).or_default();
+      *amount = amount.checked_add(asset.amount)?;
+    }
+  }
+  
+  for (denom, amount) in natives {
+    match attached.remove(denom) {
+      None => return Err(StdError::generic_err(format!("Missing funds: {} {}", amount, Fungible::Coin(denom.to_string())))),
+      Some(actual) if actual != amount => return Err(StdError::generic_err(format!(
+        "Wrong amount of {}: expected {}, got {}",
+        Fungible::Coin(denom.to_string()), amount, actual,
+      ))),
+      Some(_) => {},
+    }
+  }
+  
+  if let Some((denom, amount)) = attached.into_iter().next() {
+    return Err(StdError::generic_err(format!("Unexpected funds: {} {}", amount, Fungible::Coin(denom.to_string()))));
+  }
+  Ok(())
+}
+
+/// The single native coin attached, erroring if none or several denoms are attached. Duplicate
+/// entries of the same denom are merged.
+pub fn exactly_one_native(info: &MessageInfo) -> StdResult<Asset> {
+  let mut assets = Asset::from_funds(&info.funds)?;
+  match assets.len() {
+    0 => Err(StdError::generic_err("No funds attached")),
+    1 => Ok(assets.remove(0)),
+    n => Err(StdError::generic_err(format!("Expected exactly one coin, got {}", n))),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use cosmwasm_std::{coin, Addr};
+  use cosmwasm_std::testing::mock_info;
+  
+  fn expected() -> Vec<Asset> {
+    vec![
+      Asset { info: Fungible::Coin("uluna".to_string()), amount: Uint128::new(100) },
+      Asset { info: Fungible::Token(Addr::unchecked("token")), amount: Uint128::new(300) },
+      Asset { info: Fungible::Coin("uusd".to_string()), amount: Uint128::new(200) },
+    ]
+  }
+  
+  #[test]
+  fn test_exact() {
+    let info = mock_info("sender", &[coin(200, "uusd"), coin(100, "uluna")]);
+    assert!(assert_funds_exact(&expected(), &info).is_ok());
+  }
+  
+  #[test]
+  fn test_missing() {
+    let info = mock_info("sender", &[coin(100, "uluna")]);
+    assert_eq!(
+      assert_funds_exact(&expected(), &info).unwrap_err(),
+      StdError::generic_err("Missing funds: 200 Coin(uusd)"),
+    );
+  }
+  
+  #[test]
+  fn test_extra() {
+    let info = mock_info("sender", &[coin(100, "uluna"), coin(200, "uusd"), coin(1, "ukrw")]);
+    assert_eq!(
+      assert_funds_exact(&expected(), &info).unwrap_err(),
+      StdError::generic_err("Unexpected funds: 1 Coin(ukrw)"),
+    );
+    
+    let tokens_only = &expected()[1..2];
+    assert!(assert_funds_exact(tokens_only, &mock_info("sender", &[coin(1, "uluna")])).is_err());
+  }
+  
+  #[test]
+  fn test_wrong_amount() {
+    let info = mock_info("sender", &[coin(100, "uluna"), coin(201, "uusd")]);
+    assert_eq!(
+      assert_funds_exact(&expected(), &info).unwrap_err(),
+      StdError::generic_err("Wrong amount of Coin(uusd): expected 200, got 201"),
+    );
+  }
+  
+  #[test]
+  fn test_exactly_one_native() {
+    let info = mock_info("sender", &[coin(100, "uluna")]);
+    assert_eq!(
+      exactly_one_native(&info).unwrap(),
+      Asset { info: Fungible::Coin("uluna".to_string()), amount: Uint128::new(100) },
+    );
+    
+    assert_eq!(
+      exactly_one_native(&mock_info("sender", &[])).unwrap_err(),
+      StdError::generic_err("No funds attached"),
+    );
+    assert_eq!(
+      exactly_one_native(&mock_info("sender", &[coin(100, "uluna"), coin(200, "uusd")])).unwrap_err(),
+      StdError::generic_err("Expected exactly one coin, got 2"),
+    );
+  }
+}
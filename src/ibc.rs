@@ -0,0 +1,83 @@
+use cosmwasm_std::{to_json_vec, Binary, ContractResult, Empty, QuerierWrapper, QueryRequest, StdError, StdResult, SystemResult};
+use crate::{proto, Fungible};
+
+/// gRPC of the IBC transfer module resolving the hash of an `ibc/...` denom.
+const DENOM_TRACE_PATH: &str = "/ibc.applications.transfer.v1.Query/DenomTrace";
+
+impl Fungible {
+  /// Base denom of an IBC voucher, e.g. `uatom` for an `ibc/...` coin received over any channel,
+  /// resolved through the denom trace of the IBC transfer module. `None` for other coins and tokens.
+  /// 
+  /// Requires the chain to allow the `DenomTrace` Stargate query.
+  pub fn ibc_base_denom(&self, querier: &QuerierWrapper) -> StdResult<Option<String>> {
+    let hash = match self {
+      Fungible::Coin(denom) => match denom.strip_prefix("ibc/") {
+        Some(hash) => hash,
+        None => return Ok(None),
+      },
+      Fungible::Token(_) => return Ok(None),
+    };
+    
+    // QueryDenomTraceRequest { string hash = 1; }
+    let request: QueryRequest<Empty> = QueryRequest::Stargate {
+      path: DENOM_TRACE_PATH.to_string(),
+      data: Binary(proto::encode_bytes(1, hash.as_bytes())),
+    };
+    let response = match querier.raw_query(&to_json_vec(&request)?) {
+      SystemResult::Ok(ContractResult::Ok(response)) => response,
+      SystemResult::Ok(ContractResult::Err(err)) => return Err(StdError::generic_err(format!("Querier contract error: {}", err))),
+      SystemResult::Err(err) => return Err(StdError::generic_err(format!("Querier system error: {}", err))),
+    };
+    
+    // QueryDenomTraceResponse { DenomTrace denom_trace = 1; }, DenomTrace { string path = 1; string base_denom = 2; }
+    let base_denom = proto::find_bytes(&response, 1)
+      .and_then(|trace| proto::find_bytes(trace, 2))
+      .ok_or_else(|| StdError::parse_err("QueryDenomTraceResponse", "Invalid protobuf"))?;
+    String::from_utf8(base_denom.to_vec())
+      .map(Some)
+      .map_err(|_| StdError::parse_err("QueryDenomTraceResponse", "Invalid base denom"))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use cosmwasm_std::{from_json, Addr, Querier, QuerierResult, SystemError};
+  
+  /// Resolves the denom traces of `transfer/channel-0/uatom` and `transfer/channel-1/uatom`.
+  struct DenomTraceQuerier;
+  
+  impl Querier for DenomTraceQuerier {
+    fn raw_query(&self, bin_request: &[u8]) -> QuerierResult {
+      let (path, data) = match from_json::<QueryRequest<Empty>>(bin_request).unwrap() {
+        QueryRequest::Stargate { path, data } => (path, data),
+        _ => panic!("unexpected query"),
+      };
+      assert_eq!(path, DENOM_TRACE_PATH);
+      
+      let trace_path = match proto::find_bytes(&data, 1).unwrap() {
+        b"A1" => "transfer/channel-0",
+        b"B2" => "transfer/channel-1",
+        _ => return SystemResult::Err(SystemError::InvalidRequest { error: "unknown hash".to_string(), request: data }),
+      };
+      let mut trace = proto::encode_bytes(1, trace_path.as_bytes());
+      trace.extend(proto::encode_bytes(2, b"uatom"));
+      SystemResult::Ok(ContractResult::Ok(Binary(proto::encode_bytes(1, &trace))))
+    }
+  }
+  
+  #[test]
+  fn test_ibc_base_denom() {
+    let querier = DenomTraceQuerier;
+    let querier = QuerierWrapper::<Empty>::new(&querier);
+    
+    let via_channel0 = Fungible::Coin("ibc/A1".to_string());
+    let via_channel1 = Fungible::Coin("ibc/B2".to_string());
+    assert_eq!(via_channel0.ibc_base_denom(&querier).unwrap(), Some("uatom".to_string()));
+    assert_eq!(via_channel1.ibc_base_denom(&querier).unwrap(), Some("uatom".to_string()));
+    
+    assert!(Fungible::Coin("ibc/C3".to_string()).ibc_base_denom(&querier).is_err());
+    assert_eq!(Fungible::Coin("uluna".to_string()).ibc_base_denom(&querier).unwrap(), None);
+    assert_eq!(Fungible::Token(Addr::unchecked("ibc/A1")).ibc_base_denom(&querier).unwrap(), None);
+  }
+}
@@ -0,0 +1,61 @@
+use cosmwasm_std::{Addr, CosmosMsg, QuerierWrapper, StdResult, Uint128};
+use crate::Fungible;
+
+mod private {
+  pub trait Sealed {}
+  impl Sealed for crate::Fungible {}
+}
+
+/// Common interface of asset identifiers, so generic code need not depend on `Fungible` itself.
+/// 
+/// Sealed for now, i.e. only implemented by `Fungible`.
+pub trait AssetIdentifier: private::Sealed {
+  /// Builds the message transferring `amount` of this asset from the contract to `recipient`.
+  fn transfer_msg(&self, recipient: &Addr, amount: Uint128) -> StdResult<CosmosMsg>;
+  
+  /// Queries the balance of `account` in this asset.
+  fn query_balance(&self, querier: &QuerierWrapper, account: &Addr) -> StdResult<Uint128>;
+  
+  /// Inner identifier, i.e. the denom of a coin or the address of a token.
+  fn denom(&self) -> &str;
+}
+
+impl AssetIdentifier for Fungible {
+  fn transfer_msg(&self, recipient: &Addr, amount: Uint128) -> StdResult<CosmosMsg> {
+    Fungible::transfer_msg(self, recipient, amount)
+  }
+  
+  fn query_balance(&self, querier: &QuerierWrapper, account: &Addr) -> StdResult<Uint128> {
+    Fungible::query_balance(self, querier, account)
+  }
+  
+  fn denom(&self) -> &str {
+    self.inner()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use cosmwasm_std::testing::MockQuerier;
+  use cosmwasm_std::{coin, BankMsg};
+  
+  fn pay<A: AssetIdentifier>(asset: &A, querier: &QuerierWrapper, payer: &Addr, recipient: &Addr) -> StdResult<CosmosMsg> {
+    let balance = asset.query_balance(querier, payer)?;
+    asset.transfer_msg(recipient, balance)
+  }
+  
+  #[test]
+  fn test_generic_pay() {
+    let querier = MockQuerier::<cosmwasm_std::Empty>::new(&[("contract", &[coin(100, "uluna")])]);
+    let querier = QuerierWrapper::new(&querier);
+    let luna = Fungible::Coin("uluna".to_string());
+    let recipient = Addr::unchecked("recipient");
+    
+    assert_eq!(luna.denom(), "uluna");
+    assert_eq!(
+      pay(&luna, &querier, &Addr::unchecked("contract"), &recipient).unwrap(),
+      BankMsg::Send { to_address: "recipient".to_string(), amount: vec![coin(100, "uluna")] }.into(),
+    );
+  }
+}
@@ -0,0 +1,147 @@
+//! Conversions from and to the asset types of other ecosystems.
+
+#[cfg(feature = "cw20")]
+use cw20::Denom;
+#[cfg(feature = "cw-asset")]
+use cw_asset::AssetInfoUnchecked;
+use cosmwasm_std::Addr;
+use crate::{Fungible, FungibleError};
+#[cfg(feature = "cw-asset")]
+use crate::FungibleUnchecked;
+
+#[cfg(feature = "cw20")]
+impl From<Fungible> for Denom {
+  fn from(fungible: Fungible) -> Self {
+    match fungible {
+      Fungible::Coin(denom) => Denom::Native(denom),
+      Fungible::Token(token) => Denom::Cw20(token),
+    }
+  }
+}
+
+#[cfg(feature = "cw20")]
+impl From<Denom> for Fungible {
+  fn from(denom: Denom) -> Self {
+    match denom {
+      Denom::Native(denom) => Fungible::Coin(denom),
+      Denom::Cw20(token) => Fungible::Token(token),
+    }
+  }
+}
+
+/// Converts the unchecked `cw-asset` type arriving in messages, erroring on kinds `Fungible` cannot
+/// represent, i.e. CW1155. Validate the result to obtain a `Fungible`.
+#[cfg(feature = "cw-asset")]
+impl TryFrom<AssetInfoUnchecked> for FungibleUnchecked {
+  type Error = FungibleError;
+  
+  fn try_from(info: AssetInfoUnchecked) -> Result<Self, Self::Error> {
+    match info {
+      AssetInfoUnchecked::Native(denom) => Ok(FungibleUnchecked::Coin(denom)),
+      AssetInfoUnchecked::Cw20(token) => Ok(FungibleUnchecked::Token(token)),
+      AssetInfoUnchecked::Cw1155(contract, id) => Err(FungibleError::UnsupportedAsset { asset: format!("cw1155:{}:{}", contract, id) }),
+      // non-exhaustive
+      info => Err(FungibleError::UnsupportedAsset { asset: format!("{:?}", info) }),
+    }
+  }
+}
+
+impl Fungible {
+  /// Parses the `native:<denom>`/`cw20:<address>` scheme of `cw-asset` based tooling, as an
+  /// alternative to the `Coin(...)`/`Token(...)` form. The address is not validated.
+  pub fn from_scheme_str(s: &str) -> Result<Fungible, FungibleError> {
+    let fungible = match s.split_once(':') {
+      Some(("native", denom)) => Fungible::Coin(denom.to_string()),
+      Some(("cw20", token)) => Fungible::Token(Addr::unchecked(token)),
+      _ => return Err(FungibleError::UnknownScheme { input: s.to_string() }),
+    };
+    
+    if fungible.inner().is_empty() {
+      return Err(FungibleError::EmptyIdentifier { kind: fungible.kind() });
+    }
+    Ok(fungible)
+  }
+  
+  /// Converts into the `cw20::Denom` spoken by many existing contracts, e.g. to embed in messages
+  /// sent to them. Conversely, `Fungible::from(denom)` gives access to this crate's builders.
+  #[cfg(feature = "cw20")]
+  pub fn to_ecosystem_denom(&self) -> Denom {
+    self.clone().into()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::FungibleKind;
+  
+  #[test]
+  fn test_from_scheme_str() {
+    assert_eq!(Fungible::from_scheme_str("native:uluna"), Ok(Fungible::Coin("uluna".to_string())));
+    assert_eq!(Fungible::from_scheme_str("native:ibc/ABC:1"), Ok(Fungible::Coin("ibc/ABC:1".to_string())));
+    assert_eq!(Fungible::from_scheme_str("cw20:juno1token"), Ok(Fungible::Token(Addr::unchecked("juno1token"))));
+  }
+  
+  #[test]
+  fn test_from_scheme_str_invalid() {
+    assert_eq!(
+      Fungible::from_scheme_str("cw1155:nft:1"),
+      Err(FungibleError::UnknownScheme { input: "cw1155:nft:1".to_string() }),
+    );
+    assert!(Fungible::from_scheme_str("uluna").is_err());
+    assert!(Fungible::from_scheme_str("Coin(uluna)").is_err());
+    assert_eq!(Fungible::from_scheme_str("cw20:"), Err(FungibleError::EmptyIdentifier { kind: FungibleKind::Token }));
+  }
+  
+  #[cfg(feature = "cw20")]
+  #[test]
+  fn test_cw20_denom() {
+    use cosmwasm_std::{to_json_binary, Uint128};
+    use serde::Serialize;
+    
+    #[derive(Serialize)]
+    #[serde(rename_all = "snake_case")]
+    enum PoolExecuteMsg {
+      WithdrawFees { denom: Denom },
+    }
+    
+    let coin = Fungible::Coin("uluna".to_string());
+    let token = Fungible::Token(Addr::unchecked("token"));
+    
+    assert_eq!(coin.to_ecosystem_denom(), Denom::Native("uluna".to_string()));
+    assert_eq!(token.to_ecosystem_denom(), Denom::Cw20(Addr::unchecked("token")));
+    assert_eq!(Fungible::from(token.to_ecosystem_denom()), token);
+    
+    let msg = to_json_binary(&PoolExecuteMsg::WithdrawFees { denom: token.to_ecosystem_denom() }).unwrap();
+    assert_eq!(msg.as_slice(), br#"{"withdraw_fees":{"denom":{"cw20":"token"}}}"#);
+    
+    // and back into this crate's builders
+    let denom = Denom::Native("uluna".to_string());
+    let recipient = Addr::unchecked("recipient");
+    assert_eq!(
+      Fungible::from(denom).transfer_msg(&recipient, Uint128::new(1)).unwrap(),
+      coin.transfer_msg(&recipient, Uint128::new(1)).unwrap(),
+    );
+  }
+  
+  #[cfg(feature = "cw-asset")]
+  #[test]
+  fn test_asset_info_unchecked() {
+    use cosmwasm_std::testing::MockApi;
+    
+    let api = MockApi::default();
+    
+    let unchecked = FungibleUnchecked::try_from(AssetInfoUnchecked::native("uluna")).unwrap();
+    assert_eq!(unchecked, FungibleUnchecked::Coin("uluna".to_string()));
+    assert_eq!(unchecked.validate(&api).unwrap(), Fungible::Coin("uluna".to_string()));
+    
+    let unchecked = FungibleUnchecked::try_from(AssetInfoUnchecked::cw20("token")).unwrap();
+    assert_eq!(unchecked, FungibleUnchecked::Token("token".to_string()));
+    assert_eq!(unchecked.validate(&api).unwrap(), Fungible::Token(Addr::unchecked("token")));
+    
+    assert_eq!(
+      FungibleUnchecked::try_from(AssetInfoUnchecked::cw1155("nft", "1")).unwrap_err(),
+      FungibleError::UnsupportedAsset { asset: "cw1155:nft:1".to_string() },
+    );
+  }
+}
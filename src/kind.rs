@@ -0,0 +1,103 @@
+use cosmwasm_std::Addr;
+use schemars::JsonSchema;
+use serde::{Serialize, Deserialize};
+use crate::{Fungible, FungibleError};
+
+/// The variant of a `Fungible`, without its inner identifier.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash, JsonSchema)]
+pub enum FungibleKind {
+  Coin,
+  Token,
+}
+
+/// Every `FungibleKind`, in declaration order.
+pub const ALL_KINDS: [FungibleKind; 2] = [FungibleKind::Coin, FungibleKind::Token];
+
+impl FungibleKind {
+  /// Iterates `ALL_KINDS`.
+  pub fn iter() -> impl Iterator<Item = FungibleKind> {
+    ALL_KINDS.into_iter()
+  }
+}
+
+impl Fungible {
+  pub fn kind(&self) -> FungibleKind {
+    match self {
+      Fungible::Coin(_) => FungibleKind::Coin,
+      Fungible::Token(_) => FungibleKind::Token,
+    }
+  }
+  
+  /// Rebuilds a `Fungible` from its raw identifier and kind, e.g. as stored by an off-chain indexer.
+  /// The address of a `Token` is not validated.
+  pub fn from_parts(identifier: String, kind: FungibleKind) -> Fungible {
+    match kind {
+      FungibleKind::Coin => Fungible::Coin(identifier),
+      FungibleKind::Token => Fungible::Token(Addr::unchecked(identifier)),
+    }
+  }
+  
+  /// The denom of a coin, or `FungibleError::WrongKind` for a token.
+  pub fn ensure_coin(&self) -> Result<&str, FungibleError> {
+    match self {
+      Fungible::Coin(coin) => Ok(coin),
+      Fungible::Token(_) => Err(FungibleError::WrongKind { expected: FungibleKind::Coin, got: FungibleKind::Token }),
+    }
+  }
+  
+  /// The address of a token, or `FungibleError::WrongKind` for a coin.
+  pub fn ensure_token(&self) -> Result<&Addr, FungibleError> {
+    match self {
+      Fungible::Coin(_) => Err(FungibleError::WrongKind { expected: FungibleKind::Token, got: FungibleKind::Coin }),
+      Fungible::Token(token) => Ok(token),
+    }
+  }
+  
+  /// Inverse of `from_parts`.
+  pub fn into_parts(self) -> (String, FungibleKind) {
+    match self {
+      Fungible::Coin(coin) => (coin, FungibleKind::Coin),
+      Fungible::Token(token) => (token.into_string(), FungibleKind::Token),
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  
+  #[test]
+  fn test_parts_roundtrip() {
+    let coin = Fungible::Coin("uluna".to_string());
+    let token = Fungible::Token(Addr::unchecked("whDAI"));
+    
+    assert_eq!(coin.clone().into_parts(), ("uluna".to_string(), FungibleKind::Coin));
+    assert_eq!(token.clone().into_parts(), ("whDAI".to_string(), FungibleKind::Token));
+    
+    for fungible in [coin, token] {
+      let (identifier, kind) = fungible.clone().into_parts();
+      assert_eq!(kind, fungible.kind());
+      assert_eq!(Fungible::from_parts(identifier, kind), fungible);
+    }
+  }
+  
+  #[test]
+  fn test_all_kinds() {
+    assert_eq!(ALL_KINDS, [FungibleKind::Coin, FungibleKind::Token]);
+    assert_eq!(FungibleKind::iter().collect::<Vec<_>>(), ALL_KINDS.to_vec());
+  }
+  
+  #[test]
+  fn test_ensure_kind() {
+    let coin = Fungible::Coin("uluna".to_string());
+    let token = Fungible::Token(Addr::unchecked("whDAI"));
+    
+    assert_eq!(coin.ensure_coin(), Ok("uluna"));
+    assert_eq!(token.ensure_token(), Ok(&Addr::unchecked("whDAI")));
+    
+    assert_eq!(token.ensure_coin(), Err(FungibleError::WrongKind { expected: FungibleKind::Coin, got: FungibleKind::Token }));
+    let err = coin.ensure_token().unwrap_err();
+    assert_eq!(err, FungibleError::WrongKind { expected: FungibleKind::Token, got: FungibleKind::Coin });
+    assert_eq!(err.to_string(), "Expected Token, got Coin");
+  }
+}
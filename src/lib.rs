@@ -1,12 +1,107 @@
-use std::{cmp::Ordering, str::FromStr, fmt::Display};
-use cosmwasm_std::{Addr, StdError};
+use std::{cmp::Ordering, hash::{Hash, Hasher}, str::FromStr, fmt::Display};
+use cosmwasm_std::{Addr, Binary, StdError};
 use cw_storage_plus::{PrimaryKey, KeyDeserialize, Key, Prefixer};
 use schemars::JsonSchema;
 use serde::{Serialize, Deserialize};
+use sha2::{Digest, Sha256};
+
+mod addr;
+
+#[cfg(feature = "arbitrary")]
+mod arbitrary;
+
+mod amount;
+
+mod asset;
+pub use asset::{deduct, parse_sdk_coin, partition, Asset, Asset256, WeightedFungible};
+
+mod asset_list;
+pub use asset_list::AssetList;
+
+#[cfg(feature = "cw20")]
+mod balances;
+#[cfg(feature = "cw20")]
+pub use balances::Balances;
+
+#[cfg(feature = "cw20")]
+mod cache;
+#[cfg(feature = "cw20")]
+pub use cache::{cache_decimals, FungibleCache};
+
+mod denom;
+pub use denom::{dedup_native, validate_denom};
+#[cfg(feature = "regex")]
+pub use denom::DenomRules;
+
+mod error;
+pub use error::FungibleError;
+
+mod funds;
+pub use funds::{assert_funds_exact, exactly_one_native};
+
+#[cfg(feature = "multi-test")]
+mod multi_test;
+#[cfg(feature = "multi-test")]
+pub use multi_test::AppExt;
+
+mod proto;
+
+mod queue;
+pub use queue::FungibleQueue;
+
+mod registry;
+pub use registry::{deregister, register};
+
+mod reply;
+
+mod serde_helpers;
+pub use serde_helpers::{as_cw20_denom, as_string_optional, deserialize_flexible, list_as_string, FungibleStringKey};
+
+mod set;
+pub use set::{dedup_sorted, FungibleInterner, FungibleSet};
+
+mod storage;
+pub use storage::{
+  classify_with_registry, clear_kind, coins_in_range, count_assets, credit, debit, first_key, keys_of_kind,
+  last_key, lock, pair_key, range_by_asset, remove_and_deindex, sum_kind, unlock, AssetKeyedRecord,
+};
+mod unchecked;
+pub use unchecked::{validate_all, FungibleUnchecked, MAX_IDENTIFIER_LEN};
+
+#[cfg(feature = "stargate")]
+mod ibc;
+
+#[cfg(feature = "cw20")]
+mod identifier;
+#[cfg(feature = "cw20")]
+pub use identifier::AssetIdentifier;
+
+mod interop;
+
+mod kind;
+pub use kind::{FungibleKind, ALL_KINDS};
+
+mod msg;
+#[cfg(feature = "cw20")]
+pub use msg::{plan_transfers, transfer_from_all_msgs, TransferPlan};
+#[cfg(feature = "cw20")]
+mod query;
+#[cfg(feature = "cw20")]
+pub use query::{query_balances, query_pair_balances};
+
+#[cfg(all(test, feature = "cw20"))]
+mod testing;
 
 pub type Result<T> = std::result::Result<T, String>;
 
-#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+/// A fungible asset, either a native coin or a CW20 token.
+/// 
+/// The address of a `Token` is assumed to be validated, e.g. by building it from a `FungibleUnchecked`.
+/// 
+/// Satisfies the bounds message fields are expected to satisfy by interface tooling such as
+/// `cw-orch` (`Serialize + DeserializeOwned + JsonSchema + Debug + Clone + PartialEq + Eq`).
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(try_from = "FungibleUnchecked")]
 pub enum Fungible {
   Coin(String),
   Token(Addr),
@@ -14,24 +109,96 @@ pub enum Fungible {
 
 impl PartialOrd for Fungible {
   fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-    if self == other {
-      return Some(Ordering::Equal);
+    Some(self.cmp(other))
+  }
+}
+
+/// Tokens sort before coins. Within a variant, assets sort by the bytes of their visible string
+/// (denom or address) rather than by whatever `Addr` compares internally.
+/// 
+/// This order is stable and matches the order of `Map<Fungible, _>` keys in storage, so it must
+/// not change.
+impl Ord for Fungible {
+  fn cmp(&self, other: &Self) -> Ordering {
+    // the storage discriminant first, only comparing the inner strings within the same variant
+    self.tag().cmp(&other.tag()).then_with(|| self.inner().cmp(other.inner()))
+  }
+}
+
+/// Consistent with `Eq`, for use in `HashMap`/`HashSet` lookups.
+/// 
+/// **Warning:** iteration order of std hash collections is randomized per instance. Contract state,
+/// messages and events must never depend on it, or execution becomes non-deterministic. Iterate a
+/// `FungibleSet` (or a `BTreeMap`) instead, which follows `Ord`.
+impl Hash for Fungible {
+  fn hash<H: Hasher>(&self, state: &mut H) {
+    self.tag().hash(state);
+    self.inner().hash(state);
+  }
+}
+
+/// Version of the `Coin(...)`/`Token(...)` string form produced by `Display`.
+/// 
+/// - `0`: inner content verbatim.
+/// - `1`: `(`, `)` and `\` within the inner content escaped with `\`.
+/// 
+/// `FromStr` keeps accepting all previous versions: unescaped delimiters are taken literally, as is
+/// an escape character not followed by an escapable character. The only legacy strings parsed
+/// differently are those whose inner content contains one of the escape sequences.
+pub const FORMAT_VERSION: u8 = 1;
+
+/// Character used to escape the wrapper delimiters within the inner content of the string form.
+const ESCAPE: char = '\\';
+
+/// Escapes `(`, `)` and the escape character itself. Content without any of these is returned as-is.
+fn escape(inner: &str) -> String {
+  let mut escaped = String::with_capacity(inner.len());
+  for c in inner.chars() {
+    if c == ESCAPE || c == '(' || c == ')' {
+      escaped.push(ESCAPE);
     }
-    
-    match (self, other) {
-      (Fungible::Coin(a), Fungible::Coin(b)) => a.partial_cmp(&b),
-      (Fungible::Token(a), Fungible::Token(b)) => a.partial_cmp(b),
-      (Fungible::Coin(_), Fungible::Token(_)) => Some(Ordering::Greater),
-      (Fungible::Token(_), Fungible::Coin(_)) => Some(Ordering::Less),
+    escaped.push(c);
+  }
+  escaped
+}
+
+/// Inverse of `escape`. An escape character not followed by a delimiter or another escape character
+/// is kept verbatim.
+fn unescape(inner: &str) -> String {
+  let mut unescaped = String::with_capacity(inner.len());
+  let mut chars = inner.chars().peekable();
+  while let Some(c) = chars.next() {
+    if c == ESCAPE {
+      if let Some(&next) = chars.peek() {
+        if next == ESCAPE || next == '(' || next == ')' {
+          unescaped.push(next);
+          chars.next();
+          continue;
+        }
+      }
     }
+    unescaped.push(c);
   }
+  unescaped
 }
 
 impl Display for Fungible {
   fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
     match self {
-      Fungible::Coin(coin) => write!(f, "Coin({})", coin),
-      Fungible::Token(addr) => write!(f, "Token({})", addr),
+      Fungible::Coin(coin) => write!(f, "Coin({})", escape(coin)),
+      Fungible::Token(addr) => write!(f, "Token({})", escape(addr.as_str())),
+    }
+  }
+}
+
+impl Fungible {
+  /// Version `0` of the string form, with the inner content verbatim, for external systems parsing
+  /// the legacy form loosely. Not reliably parseable, as the inner content may contain parentheses:
+  /// prefer `Display` unless compatibility requires otherwise.
+  pub fn to_string_unescaped(&self) -> String {
+    match self {
+      Fungible::Coin(coin) => format!("Coin({})", coin),
+      Fungible::Token(addr) => format!("Token({})", addr),
     }
   }
 }
@@ -44,24 +211,24 @@ impl From<Fungible> for String {
 
 impl<'a> From<&'a Fungible> for String {
   fn from(fungible: &'a Fungible) -> String {
-    match fungible {
-      Fungible::Coin(coin) => format!("Coin({})", coin),
-      Fungible::Token(token) => format!("Token({})", token.to_string()),
-    }
+    fungible.to_string()
   }
 }
 
+/// Parses the `Display` form. The address of a `Token` is taken as-is, hence this is only meant for
+/// trusted input: parse untrusted input into a `FungibleUnchecked` instead, which must be validated
+/// before use.
 impl FromStr for Fungible {
   type Err = String;
   
   fn from_str(s: &str) -> Result<Self> {
     if s.starts_with("Coin(") && s.ends_with(')') {
       let coin = &s[5..s.len() - 1];
-      Ok(Fungible::Coin(coin.to_string()))
+      Ok(Fungible::Coin(unescape(coin)))
     }
     else if s.starts_with("Token(") && s.ends_with(')') {
       let token = &s[6..s.len() - 1];
-      Ok(Fungible::Token(Addr::unchecked(token)))
+      Ok(Fungible::Token(Addr::unchecked(unescape(token))))
     }
     else {
       Err(format!("Invalid fungible: {}", s))
@@ -69,47 +236,173 @@ impl FromStr for Fungible {
   }
 }
 
+/// Storage discriminant of `Fungible::Token`. Tokens sort before coins, both in `Ord` and in storage.
+const TOKEN_TAG: u8 = 0;
+/// Storage discriminant of `Fungible::Coin`.
+const COIN_TAG: u8 = 1;
+
+impl Fungible {
+  /// Storage discriminant of this variant, used as the first element of its `PrimaryKey`.
+  fn tag(&self) -> u8 {
+    match self {
+      Fungible::Token(_) => TOKEN_TAG,
+      Fungible::Coin(_) => COIN_TAG,
+    }
+  }
+  
+  /// Inner identifier, i.e. the denom of a `Coin` or the address of a `Token`.
+  pub(crate) fn inner(&self) -> &str {
+    match self {
+      Fungible::Coin(coin) => coin,
+      Fungible::Token(token) => token.as_str(),
+    }
+  }
+  
+  /// Owned key bytes as stored by a `Map<Fungible, _>` below its namespace: the length-prefixed
+  /// discriminant followed by the inner identifier.
+  pub fn to_key_bytes(&self) -> Vec<u8> {
+    self.joined_key()
+  }
+  
+  /// Owned counterpart of `PrimaryKey::key`, i.e. the discriminant and inner identifier segments,
+  /// which can outlive `self`, e.g. to cache keys across calls. cw-storage-plus has no owned `Key`
+  /// variant, so the segments are raw bytes to re-borrow as `Key::Ref` or pass to `Path::new`.
+  /// Prefer `key` on hot paths, as this allocates.
+  pub fn owned_key(&self) -> Vec<Vec<u8>> {
+    vec![vec![self.tag()], self.inner().as_bytes().to_vec()]
+  }
+  
+  /// Length of `to_key_bytes` without allocating it. Useful to bound the key size of stored assets.
+  pub fn key_len(&self) -> usize {
+    // 2 bytes length prefix + 1 byte discriminant
+    3 + self.inner().len()
+  }
+  
+  /// Inverse of `to_key_bytes`.
+  pub fn from_key_bytes(bytes: &[u8]) -> cosmwasm_std::StdResult<Self> {
+    Self::from_vec(bytes.to_vec())
+  }
+  
+  /// Splits a `Fungible`, i.e. its two length-prefixed key segments, off the front of a composite
+  /// key, returning the remaining bytes.
+  pub(crate) fn split_key_prefix(bytes: &[u8]) -> cosmwasm_std::StdResult<(Fungible, &[u8])> {
+    let invalid = || StdError::parse_err("Fungible", "Invalid key length");
+    if bytes.len() < 5 {
+      return Err(invalid());
+    }
+    
+    let len = u16::from_be_bytes([bytes[3], bytes[4]]) as usize;
+    let inner = bytes.get(5..5 + len).ok_or_else(invalid)?;
+    let fungible = Fungible::from_vec([&bytes[..3], inner].concat())?;
+    Ok((fungible, &bytes[5 + len..]))
+  }
+  
+  /// Deterministic identifier such as `c:uluna` or `t:terra1...`, suitable as an external cache or
+  /// database key. Unlike `Hash`, this format is guaranteed not to change across versions.
+  pub fn stable_id(&self) -> String {
+    match self {
+      Fungible::Coin(coin) => format!("c:{}", coin),
+      Fungible::Token(token) => format!("t:{}", token),
+    }
+  }
+  
+  /// Short tag of 8 hex characters, e.g. for UI badges, taken from the SHA-256 of `to_key_bytes`.
+  /// Stable across runs and versions, and distinct between a coin and a token of equal identifier.
+  /// Too short to be collision-resistant, so never use it as a key.
+  pub fn short_id(&self) -> String {
+    Sha256::digest(self.to_key_bytes())[..4].iter()
+      .map(|byte| format!("{:02x}", byte))
+      .collect()
+  }
+  
+  /// Flat `(tag, identifier)` row, e.g. for SQL databases. The tag is the storage discriminant, i.e.
+  /// `0` for tokens and `1` for coins.
+  pub fn to_row(&self) -> (u8, String) {
+    (self.tag(), self.inner().to_string())
+  }
+  
+  /// Inverse of `to_row`.
+  pub fn from_row(tag: u8, identifier: String) -> cosmwasm_std::StdResult<Self> {
+    match tag {
+      COIN_TAG => Ok(Fungible::Coin(identifier)),
+      TOKEN_TAG => Ok(Fungible::Token(Addr::unchecked(identifier))),
+      _ => Err(StdError::generic_err(format!("Invalid asset tag: {}", tag))),
+    }
+  }
+}
+
+/// Key bytes as stored by a `Map<Fungible, _>`, for raw `Storage` access.
+impl From<&Fungible> for Binary {
+  fn from(fungible: &Fungible) -> Self {
+    Binary(fungible.to_key_bytes())
+  }
+}
+
+impl TryFrom<&Binary> for Fungible {
+  type Error = StdError;
+  
+  fn try_from(bytes: &Binary) -> cosmwasm_std::StdResult<Self> {
+    Fungible::from_key_bytes(bytes.as_slice())
+  }
+}
+
+/// Decodes raw key bytes, e.g. read from a state dump, as by `from_key_bytes`.
+impl TryFrom<&[u8]> for Fungible {
+  type Error = StdError;
+  
+  fn try_from(bytes: &[u8]) -> cosmwasm_std::StdResult<Self> {
+    Fungible::from_key_bytes(bytes)
+  }
+}
+
 impl KeyDeserialize for Fungible {
   type Output = Self;
 
   fn from_vec(value: Vec<u8>) -> cosmwasm_std::StdResult<Self::Output> {
-    match value[0] {
-      0 => Ok(Fungible::Coin(String::from_vec(value[1..].to_vec()).unwrap())),
-      1 => Ok(Fungible::Token(Addr::from_vec(value[1..].to_vec()).unwrap())),
-      _ => Err(StdError::ParseErr {
-        target_type: "Fungible".to_string(),
-        msg: "Invalid type byte".to_string(),
-      }),
+    let parse_err = |msg: &str| StdError::ParseErr {
+      target_type: "Fungible".to_string(),
+      msg: msg.to_string(),
+    };
+    
+    if value.len() < 3 || value[0..2] != [0u8, 1u8] {
+      return Err(parse_err("Invalid key length"));
+    }
+    
+    let inner = value[3..].to_vec();
+    match value[2] {
+      COIN_TAG => Ok(Fungible::Coin(String::from_vec(inner)?)),
+      TOKEN_TAG => Ok(Fungible::Token(Addr::from_vec(inner)?)),
+      _ => Err(parse_err("Invalid type byte")),
     }
   }
 }
 
+/// Two key segments, the discriminant and the inner identifier, borrowed from the asset. In a
+/// composite key, cw-storage-plus only decodes a `Fungible` as the last element, so list maps keyed
+/// by `(Fungible, _)` with `range_by_asset`.
 impl<'a> PrimaryKey<'a> for Fungible {
   type Prefix = u8;
   type SubPrefix = ();
-  type Suffix = Self;
+  type Suffix = String;
   type SuperSuffix = Self;
 
-  fn key(&self) -> Vec<cw_storage_plus::Key> {
-    match self {
-      Fungible::Coin(coin) => vec![Key::Ref(coin.as_bytes())],
-      Fungible::Token(token) => vec![Key::Ref(token.as_bytes())],
-    }
+  fn key(&self) -> Vec<cw_storage_plus::Key<'_>> {
+    vec![Key::Val8([self.tag()]), Key::Ref(self.inner().as_bytes())]
   }
 }
 
+/// Prefixes by the exact asset, i.e. both the discriminant and the inner identifier, so that
+/// composite keys such as `(Fungible, _)` can be scanned per asset.
 impl<'a> Prefixer<'a> for Fungible {
-  fn prefix(&self) -> Vec<Key> {
-    match self {
-      Fungible::Coin(_) => vec![Key::Val8([0u8])],
-      Fungible::Token(_) => vec![Key::Val8([1u8])],
-    }
+  fn prefix(&self) -> Vec<Key<'_>> {
+    self.key()
   }
 }
 
 #[cfg(test)]
 mod tests {
   use super::*;
+  use cosmwasm_std::{Order, StdResult, Storage};
   use cosmwasm_std::testing::MockStorage;
   use cw_storage_plus::Map;
   
@@ -137,6 +430,21 @@ mod tests {
     assert!(token2 > token1);
   }
   
+  #[test]
+  fn test_token_ordering() {
+    // pinned to the address strings, as storage order depends on it
+    let a = Fungible::Token(Addr::unchecked("terra1abc"));
+    let b = Fungible::Token(Addr::unchecked("terra1abd"));
+    let upper = Fungible::Token(Addr::unchecked("TERRA1abc"));
+    
+    assert_eq!(a.cmp(&b), Ordering::Less);
+    assert_eq!(upper.cmp(&a), Ordering::Less);
+    
+    let mut tokens = vec![b.clone(), a.clone(), upper.clone()];
+    tokens.sort();
+    assert_eq!(tokens, vec![upper, a, b]);
+  }
+  
   #[test]
   fn test_storage_primarykey() {
     let mut store = MockStorage::new();
@@ -144,7 +452,83 @@ mod tests {
     let coin = Fungible::Coin("uluna".to_string());
     
     map.save(&mut store, coin.clone(), &"abc".to_string()).unwrap();
-    assert_eq!(map.load(&mut store, coin.clone()).unwrap(), "abc".to_string());
+    assert_eq!(map.load(&store, coin.clone()).unwrap(), "abc".to_string());
+  }
+  
+  #[test]
+  fn test_storage_temporary_key() {
+    fn save_balance(store: &mut dyn Storage, denom: &str, amount: u128) {
+      let map = Map::<Fungible, u128>::new("balances");
+      map.save(store, Fungible::Coin(denom.to_string()), &amount).unwrap();
+    }
+    
+    let mut store = MockStorage::new();
+    save_balance(&mut store, "uluna", 100);
+    
+    let map = Map::<Fungible, u128>::new("balances");
+    assert_eq!(map.load(&store, Fungible::Coin("uluna".to_string())).unwrap(), 100);
+  }
+  
+  #[test]
+  fn test_storage_range() {
+    let mut store = MockStorage::new();
+    let map = Map::<Fungible, u8>::new("test");
+    let coin = Fungible::Coin("a".to_string());
+    let token = Fungible::Token(Addr::unchecked("a"));
+    
+    map.save(&mut store, coin.clone(), &1).unwrap();
+    map.save(&mut store, token.clone(), &2).unwrap();
+    
+    let keys = map.keys(&store, None, None, Order::Ascending).collect::<StdResult<Vec<_>>>().unwrap();
+    assert_eq!(keys, vec![token.clone(), coin.clone()]);
+    
+    let coins = map.prefix(COIN_TAG).keys(&store, None, None, Order::Ascending).collect::<StdResult<Vec<_>>>().unwrap();
+    assert_eq!(coins, vec!["a".to_string()]);
+  }
+  
+  #[test]
+  fn test_key_bytes() {
+    let coin = Fungible::Coin("uluna".to_string());
+    let token = Fungible::Token(Addr::unchecked("whDAI"));
+    
+    assert_eq!(coin.to_key_bytes(), b"\x00\x01\x01uluna".to_vec());
+    assert_eq!(token.to_key_bytes(), b"\x00\x01\x00whDAI".to_vec());
+    assert_eq!(Fungible::from_key_bytes(&coin.to_key_bytes()).unwrap(), coin);
+    assert_eq!(Fungible::from_key_bytes(&token.to_key_bytes()).unwrap(), token);
+  }
+  
+  #[test]
+  fn test_owned_key() {
+    use cw_storage_plus::Path;
+    
+    let mut store = MockStorage::new();
+    let map = Map::<Fungible, u64>::new("map");
+    
+    // the keys outlive the assets they were taken from
+    let keys = {
+      let assets = [Fungible::Coin("uluna".to_string()), Fungible::Token(Addr::unchecked("whDAI"))];
+      assets.iter().map(Fungible::owned_key).collect::<Vec<_>>()
+    };
+    for (i, key) in keys.iter().enumerate() {
+      let segments = key.iter().map(Vec::as_slice).collect::<Vec<_>>();
+      Path::<u64>::new(b"map", &segments).save(&mut store, &(i as u64)).unwrap();
+    }
+    
+    assert_eq!(map.load(&store, Fungible::Coin("uluna".to_string())).unwrap(), 0);
+    assert_eq!(map.load(&store, Fungible::Token(Addr::unchecked("whDAI"))).unwrap(), 1);
+    
+    let cached = Fungible::Coin("uusd".to_string()).to_key_bytes();
+    store.set(&[b"\x00\x03map".as_slice(), &cached].concat(), &cosmwasm_std::to_json_vec(&2u64).unwrap());
+    assert_eq!(map.load(&store, Fungible::Coin("uusd".to_string())).unwrap(), 2);
+  }
+  
+  #[test]
+  fn test_key_len() {
+    let coin = Fungible::Coin("uluna".to_string());
+    let token = Fungible::Token(Addr::unchecked("whDAI"));
+    
+    assert_eq!(coin.key_len(), coin.to_key_bytes().len());
+    assert_eq!(token.key_len(), token.to_key_bytes().len());
   }
   
   #[test]
@@ -155,7 +539,72 @@ mod tests {
     let token = Fungible::Token(Addr::unchecked("whDAI"));
     
     map.save(&mut store, (coin.clone(), token.clone()), &"abc".to_string()).unwrap();
-    assert_eq!(map.load(&mut store, (coin.clone(), token.clone())).unwrap(), "abc".to_string());
+    assert_eq!(map.load(&store, (coin.clone(), token.clone())).unwrap(), "abc".to_string());
+  }
+  
+  #[test]
+  fn test_storage_tupleprefix() {
+    let mut store = MockStorage::new();
+    let map = Map::<(Fungible, String), u8>::new("test");
+    let a = Fungible::Coin("a".to_string());
+    let b = Fungible::Coin("b".to_string());
+    
+    map.save(&mut store, (a.clone(), "x".to_string()), &1).unwrap();
+    map.save(&mut store, (a.clone(), "y".to_string()), &2).unwrap();
+    map.save(&mut store, (b.clone(), "x".to_string()), &3).unwrap();
+    
+    let entries = map.prefix(a).range(&store, None, None, Order::Ascending).collect::<StdResult<Vec<_>>>().unwrap();
+    assert_eq!(entries, vec![("x".to_string(), 1), ("y".to_string(), 2)]);
+    
+    let entries = map.prefix(b).range(&store, None, None, Order::Ascending).collect::<StdResult<Vec<_>>>().unwrap();
+    assert_eq!(entries, vec![("x".to_string(), 3)]);
+  }
+  
+  #[test]
+  fn test_storage_tuple_roundtrip() {
+    let mut store = MockStorage::new();
+    let map = Map::<(Fungible, String), u8>::new("test");
+    let coin = Fungible::Coin("a".to_string());
+    let token = Fungible::Token(Addr::unchecked("a"));
+    
+    map.save(&mut store, (coin.clone(), "x".to_string()), &1).unwrap();
+    map.save(&mut store, (token.clone(), "y".to_string()), &2).unwrap();
+    map.save(&mut store, (token.clone(), "x".to_string()), &3).unwrap();
+    
+    let expected = vec![
+      ((token.clone(), "x".to_string()), 3),
+      ((token.clone(), "y".to_string()), 2),
+      ((coin.clone(), "x".to_string()), 1),
+    ];
+    assert_eq!(range_by_asset(&map, &store, Order::Ascending).unwrap(), expected);
+    
+    // consistent with per-asset prefix scans
+    let entries = map.prefix(token.clone()).range(&store, None, None, Order::Ascending).collect::<StdResult<Vec<_>>>().unwrap();
+    assert_eq!(entries, vec![("x".to_string(), 3), ("y".to_string(), 2)]);
+  }
+  
+  #[test]
+  fn test_storage_tuplekey_range() {
+    let mut store = MockStorage::new();
+    let coin = Fungible::Coin("uluna".to_string());
+    let token = Fungible::Token(Addr::unchecked("whDAI"));
+    
+    let pairs = Map::<(Fungible, Fungible), u8>::new("pairs");
+    pairs.save(&mut store, (coin.clone(), token.clone()), &1).unwrap();
+    pairs.save(&mut store, (token.clone(), coin.clone()), &2).unwrap();
+    assert_eq!(
+      range_by_asset(&pairs, &store, Order::Ascending).unwrap(),
+      vec![((token.clone(), coin.clone()), 2), ((coin.clone(), token.clone()), 1)],
+    );
+    
+    let heights = Map::<(Fungible, u64), u8>::new("heights");
+    heights.save(&mut store, (coin.clone(), 2), &1).unwrap();
+    heights.save(&mut store, (coin.clone(), 1), &2).unwrap();
+    heights.save(&mut store, (token.clone(), 3), &3).unwrap();
+    assert_eq!(
+      range_by_asset(&heights, &store, Order::Descending).unwrap(),
+      vec![((coin.clone(), 2), 1), ((coin.clone(), 1), 2), ((token.clone(), 3), 3)],
+    );
   }
   
   #[test]
@@ -167,6 +616,62 @@ mod tests {
     assert_eq!(token.to_string(), "Token(whDAI)");
   }
   
+  #[test]
+  fn test_stable_id() {
+    assert_eq!(Fungible::Coin("uluna".to_string()).stable_id(), "c:uluna");
+    assert_eq!(Fungible::Token(Addr::unchecked("whDAI")).stable_id(), "t:whDAI");
+  }
+  
+  #[test]
+  fn test_short_id() {
+    let coin = Fungible::Coin("uluna".to_string());
+    let token = Fungible::Token(Addr::unchecked("uluna"));
+    
+    assert_eq!(coin.short_id(), "c9e95f8d");
+    assert_eq!(coin.short_id(), Fungible::Coin("uluna".to_string()).short_id());
+    assert_ne!(coin.short_id(), token.short_id());
+    assert!(token.short_id().chars().all(|c| c.is_ascii_hexdigit()));
+  }
+  
+  #[test]
+  fn test_binary_key() {
+    for fungible in [Fungible::Coin("uluna".to_string()), Fungible::Token(Addr::unchecked("whDAI"))] {
+      let binary = Binary::from(&fungible);
+      assert_eq!(binary.as_slice(), fungible.joined_key().as_slice());
+      assert_eq!(Fungible::try_from(&binary).unwrap(), fungible);
+    }
+    
+    assert!(Fungible::try_from(&Binary(vec![0, 1, 2, b'a'])).is_err());
+    assert!(Fungible::try_from(&Binary(vec![])).is_err());
+  }
+  
+  #[test]
+  fn test_try_from_slice() {
+    let raw = b"\x00\x01\x01uluna".to_vec();
+    assert_eq!(Fungible::try_from(&raw[..]).unwrap(), Fungible::Coin("uluna".to_string()));
+    assert_eq!(Fungible::try_from(&b"\x00\x01\x00whDAI"[..]).unwrap(), Fungible::Token(Addr::unchecked("whDAI")));
+    
+    let parse_err = |msg: &str| StdError::parse_err("Fungible", msg);
+    assert_eq!(Fungible::try_from(&b""[..]).unwrap_err(), parse_err("Invalid key length"));
+    assert_eq!(Fungible::try_from(&b"\x00\x01\x02uluna"[..]).unwrap_err(), parse_err("Invalid type byte"));
+  }
+  
+  #[test]
+  fn test_row() {
+    let coin = Fungible::Coin("uluna".to_string());
+    let token = Fungible::Token(Addr::unchecked("whDAI"));
+    
+    assert_eq!(coin.to_row(), (1, "uluna".to_string()));
+    assert_eq!(token.to_row(), (0, "whDAI".to_string()));
+    
+    let (tag, identifier) = coin.to_row();
+    assert_eq!(Fungible::from_row(tag, identifier).unwrap(), coin);
+    let (tag, identifier) = token.to_row();
+    assert_eq!(Fungible::from_row(tag, identifier).unwrap(), token);
+    
+    assert_eq!(Fungible::from_row(2, "uluna".to_string()).unwrap_err(), StdError::generic_err("Invalid asset tag: 2"));
+  }
+  
   #[test]
   fn test_parse() {
     let coin_str = "Coin(uluna)";
@@ -175,4 +680,259 @@ mod tests {
     assert_eq!(Fungible::from_str(coin_str).unwrap(), Fungible::Coin("uluna".to_string()));
     assert_eq!(Fungible::from_str(token_str).unwrap(), Fungible::Token(Addr::unchecked("whDAI")));
   }
+  
+  #[test]
+  fn test_roundtrip_curated() {
+    let inners = ["", " ", "(", ")", "()", ")(", "\\", "\\\\", "\\(", "a\\", "Coin(a)", "Token(", "u luna", "\t\n", "ü(ñ)"];
+    for inner in inners {
+      for fungible in [Fungible::Coin(inner.to_string()), Fungible::Token(Addr::unchecked(inner))] {
+        assert_eq!(Fungible::from_str(&fungible.to_string()).unwrap(), fungible);
+      }
+    }
+  }
+  
+  proptest::proptest! {
+    #![proptest_config(proptest::prelude::ProptestConfig::with_cases(2000))]
+    
+    #[test]
+    fn test_roundtrip_coin(inner in ".*") {
+      let fungible = Fungible::Coin(inner);
+      proptest::prop_assert_eq!(Fungible::from_str(&fungible.to_string()).unwrap(), fungible);
+    }
+    
+    #[test]
+    fn test_roundtrip_token(inner in ".*") {
+      let fungible = Fungible::Token(Addr::unchecked(inner));
+      proptest::prop_assert_eq!(Fungible::from_str(&fungible.to_string()).unwrap(), fungible);
+    }
+  }
+  
+  #[test]
+  fn test_parse_legacy() {
+    // version 0
+    assert_eq!(Fungible::from_str("Coin(a)b)").unwrap(), Fungible::Coin("a)b".to_string()));
+    assert_eq!(Fungible::from_str("Coin(a(b)").unwrap(), Fungible::Coin("a(b".to_string()));
+    assert_eq!(Fungible::from_str("Token(a\\b)").unwrap(), Fungible::Token(Addr::unchecked("a\\b")));
+    
+    // version 1
+    assert_eq!(Fungible::from_str("Coin(a)b)").unwrap(), Fungible::from_str("Coin(a\\)b)").unwrap());
+    assert_eq!(Fungible::from_str("Coin(a(b)").unwrap(), Fungible::from_str("Coin(a\\(b)").unwrap());
+    assert_eq!(Fungible::from_str("Coin(uluna)").unwrap(), Fungible::Coin("uluna".to_string()));
+  }
+  
+  #[test]
+  fn test_escape_roundtrip() {
+    let open = Fungible::Coin("a(b".to_string());
+    let close = Fungible::Coin("a)b".to_string());
+    let backslash = Fungible::Token(Addr::unchecked("a\\b"));
+    let nested = Fungible::Coin("(\\)".to_string());
+    
+    assert_eq!(open.to_string(), "Coin(a\\(b)");
+    assert_eq!(close.to_string(), "Coin(a\\)b)");
+    assert_eq!(backslash.to_string(), "Token(a\\\\b)");
+    
+    for fungible in [open, close, backslash, nested] {
+      assert_eq!(Fungible::from_str(&fungible.to_string()).unwrap(), fungible);
+    }
+  }
+  
+  #[test]
+  fn test_unescaped() {
+    let paren = Fungible::Coin("a(b)".to_string());
+    assert_eq!(paren.to_string(), "Coin(a\\(b\\))");
+    assert_eq!(paren.to_string_unescaped(), "Coin(a(b))");
+    
+    let plain = Fungible::Token(Addr::unchecked("whDAI"));
+    assert_eq!(plain.to_string_unescaped(), plain.to_string());
+  }
+  
+  #[test]
+  fn test_binary_formats() {
+    // non-self-describing formats round-trip the externally tagged representation as well
+    let assets = [
+      Fungible::Coin("uluna".to_string()),
+      Fungible::Token(Addr::unchecked("whDAI")),
+      Fungible::Coin("ibc/(27394FB)".to_string()),
+    ];
+    
+    for asset in assets {
+      let bytes = bincode::serialize(&asset).unwrap();
+      assert_eq!(bincode::deserialize::<Fungible>(&bytes).unwrap(), asset);
+      
+      let bytes = rmp_serde::to_vec(&asset).unwrap();
+      assert_eq!(rmp_serde::from_slice::<Fungible>(&bytes).unwrap(), asset);
+      let bytes = rmp_serde::to_vec_named(&asset).unwrap();
+      assert_eq!(rmp_serde::from_slice::<Fungible>(&bytes).unwrap(), asset);
+    }
+    
+    let empty = bincode::serialize(&FungibleUnchecked::Coin(String::new())).unwrap();
+    assert!(bincode::deserialize::<Fungible>(&empty).is_err());
+  }
+  
+  #[test]
+  fn test_schema() {
+    use cosmwasm_schema::{cw_serde, schema_for};
+    
+    #[cw_serde]
+    enum ExecuteMsg {
+      Swap {
+        offer: Fungible,
+        ask: Fungible,
+      },
+    }
+    
+    let schema = schema_for!(ExecuteMsg);
+    match &schema.definitions["Fungible"] {
+      schemars::schema::Schema::Object(fungible) => {
+        let variants = fungible.subschemas.as_ref().unwrap().one_of.as_ref().unwrap();
+        assert_eq!(variants.len(), 2);
+      },
+      _ => panic!("Fungible schema is not an object"),
+    }
+  }
+  
+  #[test]
+  fn test_message_bounds() {
+    use serde::de::DeserializeOwned;
+    
+    fn assert_message<T>(msg: T) -> T
+    where T: Serialize + DeserializeOwned + JsonSchema + std::fmt::Debug + Clone + PartialEq + Eq
+    {
+      cosmwasm_std::from_json(cosmwasm_std::to_json_vec(&msg).unwrap()).unwrap()
+    }
+    
+    #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+    #[serde(rename_all = "snake_case")]
+    enum ExecuteMsg {
+      Deposit { asset: Fungible },
+    }
+    
+    let msg = ExecuteMsg::Deposit { asset: Fungible::Coin("uluna".to_string()) };
+    assert_eq!(assert_message(msg.clone()), msg);
+  }
+}
+
+/// Pins every ordering relationship of `Fungible`.
+/// 
+/// Tokens deliberately sort before coins. The storage discriminants follow the same order (tokens
+/// `0`, coins `1`), so `Ord` agrees with the key order of `Map<Fungible, _>`: pagination, range
+/// bounds and `first_key`/`last_key` all rely on it, and so does any canonically ordered key such
+/// as an asset pair. Flipping it would silently reorder existing state, so do not "fix" it.
+#[cfg(test)]
+mod ordering_tests {
+  use super::*;
+  
+  fn coin(denom: &str) -> Fungible {
+    Fungible::Coin(denom.to_string())
+  }
+  
+  fn token(addr: &str) -> Fungible {
+    Fungible::Token(Addr::unchecked(addr))
+  }
+  
+  #[test]
+  fn test_equality() {
+    assert_eq!(coin("uluna").cmp(&coin("uluna")), Ordering::Equal);
+    assert_eq!(token("token").cmp(&token("token")), Ordering::Equal);
+    
+    // same inner string, different variants
+    assert_ne!(coin("abc"), token("abc"));
+  }
+  
+  #[test]
+  fn test_coin_coin() {
+    // by the denom bytes
+    assert_eq!(coin("uatom").cmp(&coin("uluna")), Ordering::Less);
+    assert_eq!(coin("uluna").cmp(&coin("uatom")), Ordering::Greater);
+    assert_eq!(coin("Z").cmp(&coin("a")), Ordering::Less);
+    assert_eq!(coin("u").cmp(&coin("uluna")), Ordering::Less);
+  }
+  
+  #[test]
+  fn test_token_token() {
+    // by the address bytes
+    assert_eq!(token("token1").cmp(&token("token2")), Ordering::Less);
+    assert_eq!(token("token2").cmp(&token("token1")), Ordering::Greater);
+  }
+  
+  #[test]
+  fn test_cross_variant() {
+    // tokens first, regardless of the inner strings
+    assert_eq!(token("zzz").cmp(&coin("aaa")), Ordering::Less);
+    assert_eq!(coin("aaa").cmp(&token("zzz")), Ordering::Greater);
+    assert_eq!(token("abc").cmp(&coin("abc")), Ordering::Less);
+  }
+  
+  #[test]
+  fn test_matches_storage() {
+    let mut assets = vec![coin("uusd"), token("b"), coin("uluna"), token("a")];
+    assets.sort();
+    assert_eq!(assets, vec![token("a"), token("b"), coin("uluna"), coin("uusd")]);
+    
+    let mut keys = assets.iter().map(Fungible::to_key_bytes).collect::<Vec<_>>();
+    keys.sort();
+    assert_eq!(keys, assets.iter().map(Fungible::to_key_bytes).collect::<Vec<_>>());
+  }
+  
+  #[test]
+  fn test_deterministic_iteration() {
+    use std::collections::HashSet;
+    
+    let assets = [coin("uusd"), token("b"), coin("uluna"), token("a")];
+    let expected = vec![token("a"), token("b"), coin("uluna"), coin("uusd")];
+    
+    // insertion order does not matter to a `FungibleSet`
+    let forward: FungibleSet = assets.iter().cloned().collect();
+    let backward: FungibleSet = assets.iter().rev().cloned().collect();
+    assert_eq!(forward.into_iter().collect::<Vec<_>>(), expected);
+    assert_eq!(backward.into_iter().collect::<Vec<_>>(), expected);
+    
+    // whereas a `HashSet` guarantees no order, and must be sorted before its contents are output
+    let hashed: HashSet<Fungible> = assets.iter().cloned().collect();
+    assert!(hashed.contains(&coin("uluna")));
+    assert!(!hashed.contains(&token("uluna")));
+    let mut sorted = hashed.into_iter().collect::<Vec<_>>();
+    sorted.sort();
+    assert_eq!(sorted, expected);
+  }
+  
+  /// The structural comparison preceding the discriminant-first `Ord`, whose order it must keep.
+  fn naive_cmp(a: &Fungible, b: &Fungible) -> Ordering {
+    match (a, b) {
+      (Fungible::Coin(a), Fungible::Coin(b)) => a.as_str().cmp(b.as_str()),
+      (Fungible::Token(a), Fungible::Token(b)) => a.as_str().cmp(b.as_str()),
+      (Fungible::Coin(_), Fungible::Token(_)) => Ordering::Greater,
+      (Fungible::Token(_), Fungible::Coin(_)) => Ordering::Less,
+    }
+  }
+  
+  proptest::proptest! {
+    #[test]
+    fn test_matches_discriminant_cmp(a in (proptest::bool::ANY, "[!-~]{0,16}"), b in (proptest::bool::ANY, "[!-~]{0,16}")) {
+      let asset = |(is_coin, inner): (bool, String)| if is_coin { coin(&inner) } else { token(&inner) };
+      let (a, b) = (asset(a), asset(b));
+      proptest::prop_assert_eq!(a.cmp(&b), naive_cmp(&a, &b));
+      proptest::prop_assert_eq!(a.cmp(&b), a.to_key_bytes().cmp(&b.to_key_bytes()));
+    }
+    
+    #[test]
+    fn test_serde_invariant(assets in proptest::collection::vec((proptest::bool::ANY, "[!-~]{1,64}"), 0..16)) {
+      // sorting before storing and re-sorting after reloading must agree
+      let mut sorted = assets.into_iter()
+        .map(|(is_coin, inner)| if is_coin { coin(&inner) } else { token(&inner) })
+        .collect::<Vec<_>>();
+      sorted.sort();
+      
+      let mut reloaded: Vec<Fungible> = cosmwasm_std::from_json(cosmwasm_std::to_json_vec(&sorted).unwrap()).unwrap();
+      proptest::prop_assert_eq!(&reloaded, &sorted);
+      reloaded.sort();
+      proptest::prop_assert_eq!(&reloaded, &sorted);
+      
+      let mut rekeyed = sorted.iter()
+        .map(|fungible| Fungible::from_key_bytes(&fungible.to_key_bytes()).unwrap())
+        .collect::<Vec<_>>();
+      rekeyed.sort();
+      proptest::prop_assert_eq!(&rekeyed, &sorted);
+    }
+  }
 }
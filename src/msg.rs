@@ -0,0 +1,432 @@
+use cosmwasm_std::{coins, Addr, Api, BankMsg, CosmosMsg, Event, MessageInfo, StdError, StdResult, Uint128};
+#[cfg(feature = "stargate")]
+use cosmwasm_std::{coin, IbcMsg, IbcTimeout};
+#[cfg(feature = "cw20")]
+use cosmwasm_std::{from_json, to_json_binary, WasmMsg};
+#[cfg(feature = "cw20")]
+use cw20::Cw20ExecuteMsg;
+use crate::{Fungible, FungibleError};
+#[cfg(feature = "cw20")]
+use crate::Asset;
+
+impl Fungible {
+  /// Builds the refund of attached native funds left over after `consumed` was used, or `None` if
+  /// nothing is left. Tokens are never refunded, as CW20 amounts are pulled exactly.
+  pub fn refund_msg(&self, info: &MessageInfo, consumed: Uint128, refund_to: &Addr) -> StdResult<Option<CosmosMsg>> {
+    match self {
+      Fungible::Coin(denom) => {
+        let attached = info.funds.iter()
+          .filter(|coin| &coin.denom == denom)
+          .map(|coin| coin.amount)
+          .try_fold(Uint128::zero(), |total, amount| total.checked_add(amount))?;
+        let remainder = attached.checked_sub(consumed)?;
+        
+        if remainder.is_zero() {
+          Ok(None)
+        }
+        else {
+          Ok(Some(BankMsg::Send {
+            to_address: refund_to.to_string(),
+            amount: coins(remainder.u128(), denom),
+          }.into()))
+        }
+      },
+      Fungible::Token(_) => Ok(None),
+    }
+  }
+  
+  /// Standardized `fungible_transfer` event logging a transfer of `amount` of this asset, with the
+  /// `asset` attribute in its `Display` form.
+  pub fn transfer_event(&self, from: &Addr, to: &Addr, amount: Uint128) -> Event {
+    Event::new("fungible_transfer")
+      .add_attribute("asset", self.to_string())
+      .add_attribute("from", from)
+      .add_attribute("to", to)
+      .add_attribute("amount", amount)
+  }
+  
+  /// Builds the `BankMsg::Send` of `amount` of this coin to `to`, validated against `api`. Errors
+  /// for an invalid recipient, a zero amount and tokens.
+  pub fn native_send_checked(&self, api: &dyn Api, to: &str, amount: Uint128) -> StdResult<CosmosMsg> {
+    match self {
+      Fungible::Coin(denom) => {
+        let to = api.addr_validate(to)
+          .map_err(|_| FungibleError::InvalidAddress { address: to.to_string() })?;
+        if amount.is_zero() {
+          return Err(StdError::generic_err(format!("Cannot send zero {}", self)));
+        }
+        
+        Ok(BankMsg::Send {
+          to_address: to.into_string(),
+          amount: coins(amount.u128(), denom),
+        }.into())
+      },
+      Fungible::Token(_) => Err(StdError::generic_err(format!("Cannot bank send {}", self))),
+    }
+  }
+  
+  /// Builds the `BankMsg::Burn` of `amount` of this coin. Errors for tokens, which are burnt
+  /// through their own CW20 `Burn` message instead, and for zero amounts.
+  pub fn burn_native_msg(&self, amount: Uint128) -> StdResult<CosmosMsg> {
+    match self {
+      Fungible::Coin(denom) => {
+        if amount.is_zero() {
+          return Err(StdError::generic_err(format!("Cannot burn zero {}", self)));
+        }
+        
+        Ok(BankMsg::Burn {
+          amount: coins(amount.u128(), denom),
+        }.into())
+      },
+      Fungible::Token(_) => Err(StdError::generic_err(format!("Cannot bank burn {}", self))),
+    }
+  }
+  
+  /// Builds an ICS-20 transfer of `amount` of this coin over `channel` to `to` on the remote chain.
+  /// Errors for tokens, which cannot be transferred over IBC without a wrapper.
+  /// 
+  /// `IbcMsg::Transfer` of `cosmwasm_std` 1.x cannot carry a memo, hence a non-empty `memo` is
+  /// rejected rather than silently dropped.
+  #[cfg(feature = "stargate")]
+  pub fn ibc_transfer_msg(&self, channel: &str, to: &str, amount: Uint128, timeout: IbcTimeout, memo: Option<String>) -> StdResult<CosmosMsg> {
+    match self {
+      Fungible::Coin(denom) => {
+        if memo.is_some_and(|memo| !memo.is_empty()) {
+          return Err(StdError::generic_err("IBC transfer memo is not supported"));
+        }
+        
+        Ok(IbcMsg::Transfer {
+          channel_id: channel.to_string(),
+          to_address: to.to_string(),
+          amount: coin(amount.u128(), denom),
+          timeout,
+        }.into())
+      },
+      Fungible::Token(_) => Err(StdError::generic_err(format!("Cannot IBC transfer {}", self))),
+    }
+  }
+  
+  /// Builds the message transferring `amount` of this asset from the contract to `recipient`: a
+  /// `BankMsg::Send` for coins and a CW20 `Transfer` for tokens.
+  #[cfg(feature = "cw20")]
+  pub fn transfer_msg(&self, recipient: &Addr, amount: Uint128) -> StdResult<CosmosMsg> {
+    match self {
+      Fungible::Coin(denom) => Ok(BankMsg::Send {
+        to_address: recipient.to_string(),
+        amount: coins(amount.u128(), denom),
+      }.into()),
+      Fungible::Token(token) => Ok(WasmMsg::Execute {
+        contract_addr: token.to_string(),
+        msg: to_json_binary(&Cw20ExecuteMsg::Transfer {
+          recipient: recipient.to_string(),
+          amount,
+        })?,
+        funds: vec![],
+      }.into()),
+    }
+  }
+  
+  /// Inverse of `transfer_msg`. Recovers the asset and amount of a single-coin `BankMsg::Send` or
+  /// a CW20 `Transfer`, and `None` for any other message.
+  #[cfg(feature = "cw20")]
+  pub fn try_from_msg(msg: &CosmosMsg) -> Option<(Fungible, Uint128)> {
+    match msg {
+      CosmosMsg::Bank(BankMsg::Send { amount, .. }) if amount.len() == 1 => {
+        Some((Fungible::Coin(amount[0].denom.clone()), amount[0].amount))
+      },
+      CosmosMsg::Wasm(WasmMsg::Execute { contract_addr, msg, .. }) => {
+        match from_json(msg).ok()? {
+          Cw20ExecuteMsg::Transfer { amount, .. } => Some((Fungible::Token(Addr::unchecked(contract_addr)), amount)),
+          _ => None,
+        }
+      },
+      _ => None,
+    }
+  }
+}
+
+/// Builds one CW20 `TransferFrom` per asset, pulling pre-approved tokens from `owner` to
+/// `recipient`. Errors on native coins, which cannot be pulled by allowance.
+#[cfg(feature = "cw20")]
+pub fn transfer_from_all_msgs(assets: &[Asset], owner: &Addr, recipient: &Addr) -> StdResult<Vec<CosmosMsg>> {
+  assets.iter()
+    .map(|asset| match &asset.info {
+      Fungible::Coin(_) => Err(StdError::generic_err(format!("Cannot pull native {}", asset.info))),
+      Fungible::Token(token) => Ok(WasmMsg::Execute {
+        contract_addr: token.to_string(),
+        msg: to_json_binary(&Cw20ExecuteMsg::TransferFrom {
+          owner: owner.to_string(),
+          recipient: recipient.to_string(),
+          amount: asset.amount,
+        })?,
+        funds: vec![],
+      }.into()),
+    })
+    .collect()
+}
+
+/// Messages of a payout along with the summary of the transferred amounts.
+#[cfg(feature = "cw20")]
+pub type TransferPlan = (Vec<CosmosMsg>, Vec<(Fungible, Uint128)>);
+
+/// Plans the payout of `assets` to `to` without any side effects, e.g. for previews: a single
+/// `BankMsg::Send` of all coins followed by one CW20 `Transfer` per token, along with the summary of
+/// the transferred amounts in the same order. Amounts of the same asset are merged. Errors on zero
+/// amounts and overflow.
+#[cfg(feature = "cw20")]
+pub fn plan_transfers(assets: &[Asset], to: &Addr) -> StdResult<TransferPlan> {
+  let mut merged = std::collections::BTreeMap::<&Fungible, Uint128>::new();
+  for asset in assets {
+    if asset.amount.is_zero() {
+      return Err(StdError::generic_err(format!("Zero amount of {}", asset.info)));
+    }
+    let amount = merged.entry(&asset.info).or_default();
+    *amount = amount.checked_add(asset.amount)?;
+  }
+  
+  // tokens sort first
+  let summary: Vec<_> = merged.into_iter()
+    .map(|(info, amount)| (info.clone(), amount))
+    .collect();
+  let (tokens, natives): (Vec<_>, Vec<_>) = summary.iter()
+    .partition(|(info, _)| matches!(info, Fungible::Token(_)));
+  
+  let mut msgs = vec![];
+  if !natives.is_empty() {
+    msgs.push(BankMsg::Send {
+      to_address: to.to_string(),
+      amount: natives.iter()
+        .map(|(info, amount)| cosmwasm_std::Coin { denom: info.inner().to_string(), amount: *amount })
+        .collect(),
+    }.into());
+  }
+  for (info, amount) in &tokens {
+    msgs.push(info.transfer_msg(to, *amount)?);
+  }
+  
+  let summary = natives.into_iter().chain(tokens).cloned().collect();
+  Ok((msgs, summary))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use cosmwasm_std::coin;
+  use cosmwasm_std::testing::mock_info;
+  
+  #[test]
+  fn test_refund_msg() {
+    let refund_to = Addr::unchecked("sender");
+    let info = mock_info("sender", &[coin(100, "uluna"), coin(50, "uusd")]);
+    let coin = Fungible::Coin("uluna".to_string());
+    
+    assert_eq!(
+      coin.refund_msg(&info, Uint128::new(60), &refund_to).unwrap(),
+      Some(BankMsg::Send { to_address: "sender".to_string(), amount: coins(40, "uluna") }.into()),
+    );
+    assert_eq!(coin.refund_msg(&info, Uint128::new(100), &refund_to).unwrap(), None);
+    assert!(coin.refund_msg(&info, Uint128::new(101), &refund_to).is_err());
+    
+    let token = Fungible::Token(Addr::unchecked("token"));
+    assert_eq!(token.refund_msg(&info, Uint128::new(60), &refund_to).unwrap(), None);
+  }
+  
+  #[test]
+  fn test_transfer_event() {
+    let from = Addr::unchecked("alice");
+    let to = Addr::unchecked("bob");
+    
+    for (asset, display) in [
+      (Fungible::Coin("uluna".to_string()), "Coin(uluna)"),
+      (Fungible::Token(Addr::unchecked("token")), "Token(token)"),
+    ] {
+      let event = asset.transfer_event(&from, &to, Uint128::new(100));
+      assert_eq!(event.ty, "fungible_transfer");
+      assert_eq!(event, Event::new("fungible_transfer").add_attributes([
+        ("asset", display),
+        ("from", "alice"),
+        ("to", "bob"),
+        ("amount", "100"),
+      ]));
+    }
+  }
+  
+  #[test]
+  fn test_native_send_checked() {
+    use cosmwasm_std::testing::MockApi;
+    
+    let api = MockApi::default();
+    let luna = Fungible::Coin("uluna".to_string());
+    
+    assert_eq!(
+      luna.native_send_checked(&api, "recipient", Uint128::new(100)).unwrap(),
+      BankMsg::Send { to_address: "recipient".to_string(), amount: coins(100, "uluna") }.into(),
+    );
+    assert_eq!(
+      luna.native_send_checked(&api, "RECIPIENT", Uint128::new(100)).unwrap_err(),
+      StdError::generic_err("Invalid address: RECIPIENT"),
+    );
+    assert_eq!(
+      luna.native_send_checked(&api, "recipient", Uint128::zero()).unwrap_err(),
+      StdError::generic_err("Cannot send zero Coin(uluna)"),
+    );
+    
+    let token = Fungible::Token(Addr::unchecked("token"));
+    assert_eq!(
+      token.native_send_checked(&api, "recipient", Uint128::new(100)).unwrap_err(),
+      StdError::generic_err("Cannot bank send Token(token)"),
+    );
+  }
+  
+  #[test]
+  fn test_burn_native_msg() {
+    let luna = Fungible::Coin("uluna".to_string());
+    assert_eq!(
+      luna.burn_native_msg(Uint128::new(100)).unwrap(),
+      BankMsg::Burn { amount: coins(100, "uluna") }.into(),
+    );
+    assert_eq!(
+      luna.burn_native_msg(Uint128::zero()).unwrap_err(),
+      StdError::generic_err("Cannot burn zero Coin(uluna)"),
+    );
+    
+    let token = Fungible::Token(Addr::unchecked("token"));
+    assert_eq!(
+      token.burn_native_msg(Uint128::new(100)).unwrap_err(),
+      StdError::generic_err("Cannot bank burn Token(token)"),
+    );
+  }
+  
+  #[cfg(feature = "stargate")]
+  #[test]
+  fn test_ibc_transfer_msg() {
+    use cosmwasm_std::Timestamp;
+    
+    let timeout = IbcTimeout::with_timestamp(Timestamp::from_seconds(100));
+    let luna = Fungible::Coin("uluna".to_string());
+    
+    assert_eq!(
+      luna.ibc_transfer_msg("channel-1", "osmo1recipient", Uint128::new(100), timeout.clone(), None).unwrap(),
+      IbcMsg::Transfer {
+        channel_id: "channel-1".to_string(),
+        to_address: "osmo1recipient".to_string(),
+        amount: coin(100, "uluna"),
+        timeout: timeout.clone(),
+      }.into(),
+    );
+    assert!(luna.ibc_transfer_msg("channel-1", "osmo1recipient", Uint128::new(100), timeout.clone(), Some(String::new())).is_ok());
+    assert!(luna.ibc_transfer_msg("channel-1", "osmo1recipient", Uint128::new(100), timeout.clone(), Some("memo".to_string())).is_err());
+    
+    let token = Fungible::Token(Addr::unchecked("token"));
+    assert_eq!(
+      token.ibc_transfer_msg("channel-1", "osmo1recipient", Uint128::new(100), timeout, None).unwrap_err(),
+      StdError::generic_err("Cannot IBC transfer Token(token)"),
+    );
+  }
+  
+  #[cfg(feature = "cw20")]
+  #[test]
+  fn test_transfer_msg_roundtrip() {
+    let recipient = Addr::unchecked("recipient");
+    let coin = Fungible::Coin("uluna".to_string());
+    let token = Fungible::Token(Addr::unchecked("token"));
+    
+    let msg = coin.transfer_msg(&recipient, Uint128::new(100)).unwrap();
+    assert_eq!(Fungible::try_from_msg(&msg), Some((coin, Uint128::new(100))));
+    
+    let msg = token.transfer_msg(&recipient, Uint128::new(200)).unwrap();
+    assert_eq!(Fungible::try_from_msg(&msg), Some((token, Uint128::new(200))));
+  }
+  
+  #[cfg(feature = "cw20")]
+  #[test]
+  fn test_try_from_msg_unrecognized() {
+    let msg: CosmosMsg = BankMsg::Send {
+      to_address: "recipient".to_string(),
+      amount: vec![coin(100, "uluna"), coin(200, "uusd")],
+    }.into();
+    assert_eq!(Fungible::try_from_msg(&msg), None);
+    
+    let msg: CosmosMsg = BankMsg::Burn { amount: coins(100, "uluna") }.into();
+    assert_eq!(Fungible::try_from_msg(&msg), None);
+  }
+  
+  #[cfg(feature = "cw20")]
+  #[test]
+  fn test_transfer_from_all_msgs() {
+    let owner = Addr::unchecked("owner");
+    let recipient = Addr::unchecked("recipient");
+    let assets = vec![
+      Asset { info: Fungible::Token(Addr::unchecked("token1")), amount: Uint128::new(100) },
+      Asset { info: Fungible::Token(Addr::unchecked("token2")), amount: Uint128::new(200) },
+    ];
+    
+    let msgs = transfer_from_all_msgs(&assets, &owner, &recipient).unwrap();
+    assert_eq!(msgs, vec![
+      WasmMsg::Execute {
+        contract_addr: "token1".to_string(),
+        msg: to_json_binary(&Cw20ExecuteMsg::TransferFrom {
+          owner: "owner".to_string(),
+          recipient: "recipient".to_string(),
+          amount: Uint128::new(100),
+        }).unwrap(),
+        funds: vec![],
+      }.into(),
+      WasmMsg::Execute {
+        contract_addr: "token2".to_string(),
+        msg: to_json_binary(&Cw20ExecuteMsg::TransferFrom {
+          owner: "owner".to_string(),
+          recipient: "recipient".to_string(),
+          amount: Uint128::new(200),
+        }).unwrap(),
+        funds: vec![],
+      }.into(),
+    ]);
+  }
+  
+  #[cfg(feature = "cw20")]
+  #[test]
+  fn test_transfer_from_all_msgs_native() {
+    let assets = vec![
+      Asset { info: Fungible::Token(Addr::unchecked("token")), amount: Uint128::new(100) },
+      Asset { info: Fungible::Coin("uluna".to_string()), amount: Uint128::new(200) },
+    ];
+    
+    assert_eq!(
+      transfer_from_all_msgs(&assets, &Addr::unchecked("owner"), &Addr::unchecked("recipient")).unwrap_err(),
+      StdError::generic_err("Cannot pull native Coin(uluna)"),
+    );
+  }
+  
+  #[cfg(feature = "cw20")]
+  #[test]
+  fn test_plan_transfers() {
+    let to = Addr::unchecked("recipient");
+    let luna = Fungible::Coin("uluna".to_string());
+    let usd = Fungible::Coin("uusd".to_string());
+    let token = Fungible::Token(Addr::unchecked("token"));
+    let assets = vec![
+      Asset { info: usd.clone(), amount: Uint128::new(100) },
+      Asset { info: token.clone(), amount: Uint128::new(200) },
+      Asset { info: luna.clone(), amount: Uint128::new(300) },
+      Asset { info: usd.clone(), amount: Uint128::new(400) },
+    ];
+    
+    let (msgs, summary) = plan_transfers(&assets, &to).unwrap();
+    assert_eq!(msgs, vec![
+      BankMsg::Send {
+        to_address: "recipient".to_string(),
+        amount: vec![coin(300, "uluna"), coin(500, "uusd")],
+      }.into(),
+      token.transfer_msg(&to, Uint128::new(200)).unwrap(),
+    ]);
+    assert_eq!(summary, vec![
+      (luna, Uint128::new(300)),
+      (usd.clone(), Uint128::new(500)),
+      (token, Uint128::new(200)),
+    ]);
+    
+    assert_eq!(plan_transfers(&[], &to).unwrap(), (vec![], vec![]));
+    assert!(plan_transfers(&[Asset { info: usd, amount: Uint128::zero() }], &to).is_err());
+  }
+}
@@ -0,0 +1,68 @@
+//! Extensions of `cw-multi-test` for integration tests of contracts using this crate.
+
+use cosmwasm_std::{coins, Addr};
+use cw20::Cw20ExecuteMsg;
+use cw_multi_test::{App, AppResponse, Executor};
+use crate::{Asset, Fungible};
+
+pub trait AppExt {
+  /// Transfers `asset` from `from` to `to`: a bank send for coins and a CW20 `Transfer` for tokens.
+  /// 
+  /// Panics if the transfer fails, as befits a test fixture.
+  fn transfer_fungible(&mut self, from: &Addr, to: &Addr, asset: &Asset) -> AppResponse;
+}
+
+impl AppExt for App {
+  fn transfer_fungible(&mut self, from: &Addr, to: &Addr, asset: &Asset) -> AppResponse {
+    let result = match &asset.info {
+      Fungible::Coin(denom) => self.send_tokens(from.clone(), to.clone(), &coins(asset.amount.u128(), denom)),
+      Fungible::Token(token) => self.execute_contract(
+        from.clone(),
+        token.clone(),
+        &Cw20ExecuteMsg::Transfer {
+          recipient: to.to_string(),
+          amount: asset.amount,
+        },
+        &[],
+      ),
+    };
+    result.unwrap_or_else(|err| panic!("Failed to transfer {} from {} to {}: {}", asset, from, to, err))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use cosmwasm_std::{coin, Uint128};
+  use crate::testing::deploy_cw20;
+  
+  #[test]
+  fn test_transfer_fungible() {
+    let alice = Addr::unchecked("alice");
+    let bob = Addr::unchecked("bob");
+    let mut app = App::new(|router, _, storage| {
+      router.bank.init_balance(storage, &alice, vec![coin(100, "uluna")]).unwrap();
+    });
+    let token = Fungible::Token(deploy_cw20(&mut app, &[("alice", 200)], None));
+    let luna = Fungible::Coin("uluna".to_string());
+    
+    app.transfer_fungible(&alice, &bob, &Asset { info: luna.clone(), amount: Uint128::new(30) });
+    app.transfer_fungible(&alice, &bob, &Asset { info: token.clone(), amount: Uint128::new(50) });
+    
+    assert_eq!(luna.query_balance(&app.wrap(), &alice).unwrap(), Uint128::new(70));
+    assert_eq!(luna.query_balance(&app.wrap(), &bob).unwrap(), Uint128::new(30));
+    assert_eq!(token.query_balance(&app.wrap(), &alice).unwrap(), Uint128::new(150));
+    assert_eq!(token.query_balance(&app.wrap(), &bob).unwrap(), Uint128::new(50));
+  }
+  
+  #[test]
+  #[should_panic(expected = "Failed to transfer 1 Coin(uluna) from alice to bob")]
+  fn test_transfer_fungible_insufficient() {
+    let mut app = App::default();
+    app.transfer_fungible(
+      &Addr::unchecked("alice"),
+      &Addr::unchecked("bob"),
+      &Asset { info: Fungible::Coin("uluna".to_string()), amount: Uint128::one() },
+    );
+  }
+}
@@ -0,0 +1,86 @@
+//! Minimal protobuf wire format support for the few Cosmos SDK messages this crate handles, to
+//! avoid depending on generated types.
+
+/// Encodes a length-delimited (wire type 2) field, e.g. a `string`.
+#[cfg(feature = "stargate")]
+pub(crate) fn encode_bytes(field: u32, bytes: &[u8]) -> Vec<u8> {
+  let mut encoded = vec![];
+  encode_varint(&mut encoded, (field as u64) << 3 | 2);
+  encode_varint(&mut encoded, bytes.len() as u64);
+  encoded.extend_from_slice(bytes);
+  encoded
+}
+
+#[cfg(feature = "stargate")]
+fn encode_varint(out: &mut Vec<u8>, mut value: u64) {
+  while value >= 0x80 {
+    out.push((value as u8 & 0x7f) | 0x80);
+    value >>= 7;
+  }
+  out.push(value as u8);
+}
+
+/// Decodes a varint at the start of `data`, returning it along with its encoded length.
+fn decode_varint(data: &[u8]) -> Option<(u64, usize)> {
+  let mut value = 0u64;
+  for (i, byte) in data.iter().enumerate().take(10) {
+    value |= ((byte & 0x7f) as u64) << (7 * i);
+    if byte & 0x80 == 0 {
+      return Some((value, i + 1));
+    }
+  }
+  None
+}
+
+/// Payload of the first length-delimited occurrence of `field` in the message `data`, skipping
+/// other fields. `None` if absent or malformed.
+pub(crate) fn find_bytes(data: &[u8], field: u32) -> Option<&[u8]> {
+  let mut offset = 0;
+  while offset < data.len() {
+    let (key, len) = decode_varint(&data[offset..])?;
+    offset += len;
+    
+    let size = match key & 0x7 {
+      0 => decode_varint(&data[offset..])?.1,
+      1 => 8,
+      2 => {
+        let (size, len) = decode_varint(&data[offset..])?;
+        offset += len;
+        if key >> 3 == field as u64 {
+          return data.get(offset..offset.checked_add(size as usize)?);
+        }
+        size as usize
+      },
+      5 => 4,
+      _ => return None,
+    };
+    offset = offset.checked_add(size)?;
+  }
+  None
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  
+  #[test]
+  fn test_find_bytes() {
+    // field 1 varint 150, field 2 "ab", field 3 "c"
+    let data = [0x08, 0x96, 0x01, 0x12, 0x02, b'a', b'b', 0x1a, 0x01, b'c'];
+    assert_eq!(find_bytes(&data, 2), Some(&b"ab"[..]));
+    assert_eq!(find_bytes(&data, 3), Some(&b"c"[..]));
+    assert_eq!(find_bytes(&data, 4), None);
+    assert_eq!(find_bytes(&[0x12, 0x05, b'a'], 2), None);
+  }
+  
+  #[cfg(feature = "stargate")]
+  #[test]
+  fn test_encode_bytes() {
+    assert_eq!(encode_bytes(1, b"ab"), vec![0x0a, 0x02, b'a', b'b']);
+    
+    let long = vec![b'a'; 300];
+    let encoded = encode_bytes(2, &long);
+    assert_eq!(&encoded[..3], &[0x12, 0xac, 0x02]);
+    assert_eq!(find_bytes(&encoded, 2), Some(&long[..]));
+  }
+}
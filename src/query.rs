@@ -0,0 +1,300 @@
+use cosmwasm_std::{Addr, QuerierWrapper, StdError, StdResult, Uint128};
+use cw20::{AllowanceResponse, BalanceResponse, Cw20QueryMsg, MarketingInfoResponse, MinterResponse, TokenInfoResponse};
+use crate::Fungible;
+
+impl Fungible {
+  /// Queries the balance of `account` in this asset.
+  /// 
+  /// Balances are only available at the current height. Contract queries, including Stargate
+  /// ones, are served against the state of the executing block, and CW20 keeps no history, so
+  /// historical balances must be snapshotted contract-side.
+  pub fn query_balance(&self, querier: &QuerierWrapper, account: &Addr) -> StdResult<Uint128> {
+    match self {
+      Fungible::Coin(denom) => Ok(querier.query_balance(account, denom)?.amount),
+      Fungible::Token(token) => {
+        let response: BalanceResponse = querier.query_wasm_smart(
+          token,
+          &Cw20QueryMsg::Balance { address: account.to_string() },
+        )?;
+        Ok(response.balance)
+      },
+    }
+  }
+  
+  /// Like `query_balance`, but **swallows any query error**, returning zero instead, e.g. for
+  /// best-effort summing where one failing token contract should not abort the whole operation.
+  /// Errors are indistinguishable from an empty balance, so never use this to gate payouts.
+  pub fn query_balance_or_zero(&self, querier: &QuerierWrapper, account: &Addr) -> Uint128 {
+    self.query_balance(querier, account).unwrap_or_default()
+  }
+  
+  /// Like `query_balance`, but first cross-checks the `TokenInfo` symbol of a token against
+  /// `expected_symbol`, if any, guarding against a misconfigured address. Coins have no symbol, so
+  /// the check is skipped for them.
+  pub fn query_balance_checked(&self, querier: &QuerierWrapper, account: &Addr, expected_symbol: Option<&str>) -> StdResult<Uint128> {
+    if let (Fungible::Token(token), Some(expected)) = (self, expected_symbol) {
+      let info: TokenInfoResponse = querier.query_wasm_smart(token, &Cw20QueryMsg::TokenInfo {})?;
+      if info.symbol != expected {
+        return Err(StdError::generic_err(format!(
+          "Symbol mismatch of {}: expected {}, got {}",
+          self, expected, info.symbol,
+        )));
+      }
+    }
+    self.query_balance(querier, account)
+  }
+  
+  /// Queries the minter of a CW20 token, if any. Always `None` for coins.
+  pub fn query_minter(&self, querier: &QuerierWrapper) -> StdResult<Option<MinterResponse>> {
+    match self {
+      Fungible::Coin(_) => Ok(None),
+      Fungible::Token(token) => querier.query_wasm_smart(token, &Cw20QueryMsg::Minter {}),
+    }
+  }
+  
+  /// Queries the marketing info of a CW20 token. Always `None` for coins.
+  pub fn query_marketing(&self, querier: &QuerierWrapper) -> StdResult<Option<MarketingInfoResponse>> {
+    match self {
+      Fungible::Coin(_) => Ok(None),
+      Fungible::Token(token) => querier.query_wasm_smart(token, &Cw20QueryMsg::MarketingInfo {}).map(Some),
+    }
+  }
+  
+  /// Whether this token responds to a CW20 `TokenInfo` query. Any query failure, such as a missing
+  /// contract or one that is not a CW20, yields `false`. Always `true` for coins, whose existence
+  /// cannot be cheaply verified.
+  pub fn exists_on_chain(&self, querier: &QuerierWrapper) -> StdResult<bool> {
+    match self {
+      Fungible::Coin(_) => Ok(true),
+      Fungible::Token(token) => Ok(
+        querier.query_wasm_smart::<TokenInfoResponse>(token, &Cw20QueryMsg::TokenInfo {}).is_ok()
+      ),
+    }
+  }
+  
+  /// Errors if `spender` may not transfer at least `needed` of this token on behalf of `owner`.
+  /// Always succeeds for coins, which have no concept of allowance.
+  pub fn ensure_allowance(&self, querier: &QuerierWrapper, owner: &Addr, spender: &Addr, needed: Uint128) -> StdResult<()> {
+    match self {
+      Fungible::Coin(_) => Ok(()),
+      Fungible::Token(token) => {
+        let response: AllowanceResponse = querier.query_wasm_smart(
+          token,
+          &Cw20QueryMsg::Allowance {
+            owner: owner.to_string(),
+            spender: spender.to_string(),
+          },
+        )?;
+        
+        if response.allowance < needed {
+          return Err(StdError::generic_err(format!(
+            "Insufficient allowance of {}: {} < {}",
+            self, response.allowance, needed,
+          )));
+        }
+        Ok(())
+      },
+    }
+  }
+}
+
+/// Queries the balances of `account` for all `assets`, aligned with the input order.
+/// 
+/// All native balances are fetched with a single `BankQuery::AllBalances`, whereas each CW20 token
+/// requires its own `Balance` smart query.
+pub fn query_balances(querier: &QuerierWrapper, account: &Addr, assets: &[Fungible]) -> StdResult<Vec<Uint128>> {
+  let natives = if assets.iter().any(|asset| matches!(asset, Fungible::Coin(_))) {
+    querier.query_all_balances(account)?
+  }
+  else {
+    vec![]
+  };
+  
+  assets.iter()
+    .map(|asset| match asset {
+      Fungible::Coin(denom) => Ok(
+        natives.iter()
+          .find(|coin| &coin.denom == denom)
+          .map(|coin| coin.amount)
+          .unwrap_or_default()
+      ),
+      Fungible::Token(_) => asset.query_balance(querier, account),
+    })
+    .collect()
+}
+
+/// Queries the balances of `account` for both assets of a pair, in order, like `query_balances`.
+pub fn query_pair_balances(querier: &QuerierWrapper, account: &Addr, a: &Fungible, b: &Fungible) -> StdResult<(Uint128, Uint128)> {
+  let balances = query_balances(querier, account, &[a.clone(), b.clone()])?;
+  Ok((balances[0], balances[1]))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use cosmwasm_std::{coin, from_json, to_json_binary, ContractResult, SystemError, SystemResult, WasmQuery};
+  use cosmwasm_std::testing::MockQuerier;
+  use cw_multi_test::{App, Executor};
+  use crate::testing::deploy_cw20;
+  
+  fn mock_querier() -> MockQuerier {
+    let mut querier = MockQuerier::new(&[("alice", &[coin(100, "uluna"), coin(200, "uusd"), coin(300, "ukrw")])]);
+    querier.update_wasm(|query| match query {
+      WasmQuery::Smart { contract_addr, msg } => {
+        let balance = match (contract_addr.as_str(), from_json(msg).unwrap()) {
+          ("token1", Cw20QueryMsg::Balance { address }) if address == "alice" => 400u128,
+          ("token2", Cw20QueryMsg::Balance { address }) if address == "alice" => 500u128,
+          (_, Cw20QueryMsg::Balance { .. }) => 0u128,
+          _ => panic!("unexpected query"),
+        };
+        SystemResult::Ok(ContractResult::Ok(to_json_binary(&BalanceResponse { balance: balance.into() }).unwrap()))
+      },
+      _ => SystemResult::Err(SystemError::UnsupportedRequest { kind: "wasm".to_string() }),
+    });
+    querier
+  }
+  
+  #[test]
+  fn test_query_balances() {
+    let querier = mock_querier();
+    let querier = QuerierWrapper::<cosmwasm_std::Empty>::new(&querier);
+    let assets = vec![
+      Fungible::Coin("uusd".to_string()),
+      Fungible::Token(Addr::unchecked("token1")),
+      Fungible::Coin("uluna".to_string()),
+      Fungible::Coin("uatom".to_string()),
+      Fungible::Token(Addr::unchecked("token2")),
+      Fungible::Coin("ukrw".to_string()),
+    ];
+    
+    let balances = query_balances(&querier, &Addr::unchecked("alice"), &assets).unwrap();
+    assert_eq!(balances, vec![
+      Uint128::new(200),
+      Uint128::new(400),
+      Uint128::new(100),
+      Uint128::zero(),
+      Uint128::new(500),
+      Uint128::new(300),
+    ]);
+  }
+  
+  #[test]
+  fn test_query_pair_balances() {
+    let querier = mock_querier();
+    let querier = QuerierWrapper::<cosmwasm_std::Empty>::new(&querier);
+    let alice = Addr::unchecked("alice");
+    let luna = Fungible::Coin("uluna".to_string());
+    let usd = Fungible::Coin("uusd".to_string());
+    let token1 = Fungible::Token(Addr::unchecked("token1"));
+    let token2 = Fungible::Token(Addr::unchecked("token2"));
+    
+    assert_eq!(query_pair_balances(&querier, &alice, &usd, &luna).unwrap(), (Uint128::new(200), Uint128::new(100)));
+    assert_eq!(query_pair_balances(&querier, &alice, &token2, &token1).unwrap(), (Uint128::new(500), Uint128::new(400)));
+    assert_eq!(query_pair_balances(&querier, &alice, &luna, &token2).unwrap(), (Uint128::new(100), Uint128::new(500)));
+    assert_eq!(query_pair_balances(&querier, &alice, &token1, &usd).unwrap(), (Uint128::new(400), Uint128::new(200)));
+  }
+  
+  #[test]
+  fn test_query_balance() {
+    let querier = mock_querier();
+    let querier = QuerierWrapper::<cosmwasm_std::Empty>::new(&querier);
+    let alice = Addr::unchecked("alice");
+    
+    assert_eq!(Fungible::Coin("uluna".to_string()).query_balance(&querier, &alice).unwrap(), Uint128::new(100));
+    assert_eq!(Fungible::Coin("uatom".to_string()).query_balance(&querier, &alice).unwrap(), Uint128::zero());
+    assert_eq!(Fungible::Token(Addr::unchecked("token1")).query_balance(&querier, &alice).unwrap(), Uint128::new(400));
+  }
+  
+  #[test]
+  fn test_query_balance_or_zero() {
+    let mut app = App::default();
+    let token = Fungible::Token(deploy_cw20(&mut app, &[("alice", 100)], None));
+    let alice = Addr::unchecked("alice");
+    
+    assert_eq!(token.query_balance_or_zero(&app.wrap(), &alice), Uint128::new(100));
+    
+    let missing = Fungible::Token(Addr::unchecked("missing"));
+    assert!(missing.query_balance(&app.wrap(), &alice).is_err());
+    assert_eq!(missing.query_balance_or_zero(&app.wrap(), &alice), Uint128::zero());
+  }
+  
+  #[test]
+  fn test_query_balance_checked() {
+    let mut app = App::default();
+    let token = Fungible::Token(deploy_cw20(&mut app, &[("alice", 100)], None));
+    let alice = Addr::unchecked("alice");
+    
+    assert_eq!(token.query_balance_checked(&app.wrap(), &alice, Some("TEST")).unwrap(), Uint128::new(100));
+    assert_eq!(token.query_balance_checked(&app.wrap(), &alice, None).unwrap(), Uint128::new(100));
+    assert_eq!(
+      token.query_balance_checked(&app.wrap(), &alice, Some("USDC")).unwrap_err(),
+      StdError::generic_err(format!("Symbol mismatch of {}: expected USDC, got TEST", token)),
+    );
+    
+    let coin = Fungible::Coin("uluna".to_string());
+    assert_eq!(coin.query_balance_checked(&app.wrap(), &alice, Some("LUNA")).unwrap(), Uint128::zero());
+  }
+  
+  #[test]
+  fn test_query_minter() {
+    let mut app = App::default();
+    let minted = Fungible::Token(deploy_cw20(&mut app, &[], Some("minter")));
+    let fixed = Fungible::Token(deploy_cw20(&mut app, &[("alice", 100)], None));
+    let coin = Fungible::Coin("uluna".to_string());
+    
+    assert_eq!(
+      minted.query_minter(&app.wrap()).unwrap(),
+      Some(MinterResponse { minter: "minter".to_string(), cap: None }),
+    );
+    assert_eq!(fixed.query_minter(&app.wrap()).unwrap(), None);
+    assert_eq!(coin.query_minter(&app.wrap()).unwrap(), None);
+  }
+  
+  #[test]
+  fn test_query_marketing() {
+    let mut app = App::default();
+    let token = Fungible::Token(deploy_cw20(&mut app, &[], Some("minter")));
+    let coin = Fungible::Coin("uluna".to_string());
+    
+    assert_eq!(token.query_marketing(&app.wrap()).unwrap(), Some(MarketingInfoResponse::default()));
+    assert_eq!(coin.query_marketing(&app.wrap()).unwrap(), None);
+  }
+  
+  #[test]
+  fn test_ensure_allowance() {
+    let mut app = App::default();
+    let token_addr = deploy_cw20(&mut app, &[("alice", 100)], None);
+    let token = Fungible::Token(token_addr.clone());
+    let alice = Addr::unchecked("alice");
+    let spender = Addr::unchecked("spender");
+    
+    app.execute_contract(
+      alice.clone(),
+      token_addr,
+      &cw20::Cw20ExecuteMsg::IncreaseAllowance {
+        spender: spender.to_string(),
+        amount: Uint128::new(50),
+        expires: None,
+      },
+      &[],
+    ).unwrap();
+    
+    assert!(token.ensure_allowance(&app.wrap(), &alice, &spender, Uint128::new(50)).is_ok());
+    assert!(token.ensure_allowance(&app.wrap(), &alice, &spender, Uint128::new(51)).is_err());
+    
+    let coin = Fungible::Coin("uluna".to_string());
+    assert!(coin.ensure_allowance(&app.wrap(), &alice, &spender, Uint128::new(1000)).is_ok());
+  }
+  
+  #[test]
+  fn test_exists_on_chain() {
+    let mut app = App::default();
+    let token = Fungible::Token(deploy_cw20(&mut app, &[], None));
+    let missing = Fungible::Token(Addr::unchecked("missing"));
+    let coin = Fungible::Coin("uluna".to_string());
+    
+    assert!(token.exists_on_chain(&app.wrap()).unwrap());
+    assert!(!missing.exists_on_chain(&app.wrap()).unwrap());
+    assert!(coin.exists_on_chain(&app.wrap()).unwrap());
+  }
+}
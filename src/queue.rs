@@ -0,0 +1,86 @@
+use cosmwasm_std::{StdResult, Storage};
+use cw_storage_plus::{Deque, Map};
+use crate::Fungible;
+
+/// A FIFO queue of distinct assets in storage, i.e. a `Deque` with a membership set for dedup.
+pub struct FungibleQueue<'a> {
+  queue: Deque<'a, Fungible>,
+  members: Map<'a, Fungible, ()>,
+}
+
+impl<'a> FungibleQueue<'a> {
+  pub const fn new(queue_namespace: &'a str, members_namespace: &'a str) -> Self {
+    FungibleQueue {
+      queue: Deque::new(queue_namespace),
+      members: Map::new(members_namespace),
+    }
+  }
+  
+  /// Enqueues `asset` unless already queued. Returns whether it was enqueued.
+  pub fn push_unique(&self, store: &mut dyn Storage, asset: &Fungible) -> StdResult<bool> {
+    if self.contains(store, asset) {
+      return Ok(false);
+    }
+    
+    self.queue.push_back(store, asset)?;
+    self.members.save(store, asset.clone(), &())?;
+    Ok(true)
+  }
+  
+  /// Dequeues the oldest asset, if any.
+  pub fn pop(&self, store: &mut dyn Storage) -> StdResult<Option<Fungible>> {
+    let asset = self.queue.pop_front(store)?;
+    if let Some(asset) = &asset {
+      self.members.remove(store, asset.clone());
+    }
+    Ok(asset)
+  }
+  
+  pub fn contains(&self, store: &dyn Storage, asset: &Fungible) -> bool {
+    self.members.has(store, asset.clone())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use cosmwasm_std::Addr;
+  use cosmwasm_std::testing::MockStorage;
+  
+  const QUEUE: FungibleQueue = FungibleQueue::new("queue", "queue_members");
+  
+  #[test]
+  fn test_push_unique() {
+    let mut store = MockStorage::new();
+    let coin = Fungible::Coin("uluna".to_string());
+    
+    assert!(!QUEUE.contains(&store, &coin));
+    assert!(QUEUE.push_unique(&mut store, &coin).unwrap());
+    assert!(!QUEUE.push_unique(&mut store, &coin).unwrap());
+    assert!(QUEUE.contains(&store, &coin));
+    
+    assert_eq!(QUEUE.pop(&mut store).unwrap(), Some(coin.clone()));
+    assert_eq!(QUEUE.pop(&mut store).unwrap(), None);
+    assert!(!QUEUE.contains(&store, &coin));
+  }
+  
+  #[test]
+  fn test_fifo() {
+    let mut store = MockStorage::new();
+    let assets = [
+      Fungible::Coin("uusd".to_string()),
+      Fungible::Token(Addr::unchecked("token")),
+      Fungible::Coin("uluna".to_string()),
+    ];
+    
+    for asset in &assets {
+      QUEUE.push_unique(&mut store, asset).unwrap();
+    }
+    QUEUE.push_unique(&mut store, &assets[0]).unwrap();
+    
+    for asset in &assets {
+      assert_eq!(QUEUE.pop(&mut store).unwrap().as_ref(), Some(asset));
+    }
+    assert_eq!(QUEUE.pop(&mut store).unwrap(), None);
+  }
+}
@@ -0,0 +1,71 @@
+//! Allowlist of supported assets emitting standardized events on changes.
+
+use cosmwasm_std::{Event, StdError, StdResult, Storage};
+use cw_storage_plus::Map;
+use crate::Fungible;
+
+/// Adds `asset` to the allowlist `map`, returning an `asset_registered` event. Errors if already
+/// registered.
+pub fn register(store: &mut dyn Storage, map: &Map<Fungible, ()>, asset: &Fungible) -> StdResult<Event> {
+  if map.has(store, asset.clone()) {
+    return Err(StdError::generic_err(format!("Already registered asset: {}", asset)));
+  }
+  
+  map.save(store, asset.clone(), &())?;
+  Ok(Event::new("asset_registered").add_attribute("asset", asset.to_string()))
+}
+
+/// Removes `asset` from the allowlist `map`, returning an `asset_deregistered` event. Errors if not
+/// registered.
+pub fn deregister(store: &mut dyn Storage, map: &Map<Fungible, ()>, asset: &Fungible) -> StdResult<Event> {
+  if !map.has(store, asset.clone()) {
+    return Err(StdError::generic_err(format!("Unregistered asset: {}", asset)));
+  }
+  
+  map.remove(store, asset.clone());
+  Ok(Event::new("asset_deregistered").add_attribute("asset", asset.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use cosmwasm_std::Addr;
+  use cosmwasm_std::testing::MockStorage;
+  
+  const REGISTRY: Map<Fungible, ()> = Map::new("registry");
+  
+  #[test]
+  fn test_register() {
+    let mut store = MockStorage::new();
+    let token = Fungible::Token(Addr::unchecked("token"));
+    
+    assert_eq!(
+      register(&mut store, &REGISTRY, &token).unwrap(),
+      Event::new("asset_registered").add_attribute("asset", "Token(token)"),
+    );
+    assert!(token.assert_registered(&store, &REGISTRY).is_ok());
+    
+    assert_eq!(
+      register(&mut store, &REGISTRY, &token).unwrap_err(),
+      StdError::generic_err("Already registered asset: Token(token)"),
+    );
+  }
+  
+  #[test]
+  fn test_deregister() {
+    let mut store = MockStorage::new();
+    let luna = Fungible::Coin("uluna".to_string());
+    
+    register(&mut store, &REGISTRY, &luna).unwrap();
+    assert_eq!(
+      deregister(&mut store, &REGISTRY, &luna).unwrap(),
+      Event::new("asset_deregistered").add_attribute("asset", "Coin(uluna)"),
+    );
+    assert!(luna.assert_registered(&store, &REGISTRY).is_err());
+    
+    assert_eq!(
+      deregister(&mut store, &REGISTRY, &luna).unwrap_err(),
+      StdError::generic_err("Unregistered asset: Coin(uluna)"),
+    );
+  }
+}
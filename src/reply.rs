@@ -0,0 +1,82 @@
+use cosmwasm_std::{Reply, StdError, StdResult};
+use crate::{proto, Fungible};
+
+/// Event emitted by the token-factory module on `MsgCreateDenom`.
+const CREATE_DENOM_EVENT: &str = "create_denom";
+/// Attribute of `CREATE_DENOM_EVENT` holding the new denom.
+const NEW_TOKEN_DENOM_ATTR: &str = "new_token_denom";
+
+impl Fungible {
+  /// Extracts the denom created by a token-factory `MsgCreateDenom` submessage from its reply.
+  /// 
+  /// The protobuf-encoded `MsgCreateDenomResponse` in the reply data takes precedence. Otherwise,
+  /// the denom is taken from the `new_token_denom` attribute of the `create_denom` event.
+  pub fn from_create_denom_reply(reply: &Reply) -> StdResult<Fungible> {
+    let response = reply.result.clone().into_result().map_err(StdError::generic_err)?;
+    
+    if let Some(data) = response.data.as_ref().filter(|data| !data.is_empty()) {
+      return parse_create_denom_response(data).map(Fungible::Coin);
+    }
+    
+    response.events.iter()
+      .filter(|event| event.ty == CREATE_DENOM_EVENT)
+      .flat_map(|event| event.attributes.iter())
+      .find(|attr| attr.key == NEW_TOKEN_DENOM_ATTR)
+      .map(|attr| Fungible::Coin(attr.value.clone()))
+      .ok_or_else(|| StdError::generic_err("No created denom in reply"))
+  }
+}
+
+/// Decodes `MsgCreateDenomResponse { string new_token_denom = 1; }`.
+fn parse_create_denom_response(data: &[u8]) -> StdResult<String> {
+  let invalid = || StdError::parse_err("MsgCreateDenomResponse", "Invalid protobuf");
+  let denom = proto::find_bytes(data, 1).ok_or_else(invalid)?;
+  String::from_utf8(denom.to_vec()).map_err(|_| invalid())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use cosmwasm_std::{Binary, Event, SubMsgResponse, SubMsgResult};
+  
+  const DENOM: &str = "factory/osmo1contract/utoken";
+  
+  fn reply(events: Vec<Event>, data: Option<Binary>) -> Reply {
+    Reply {
+      id: 1,
+      result: SubMsgResult::Ok(SubMsgResponse { events, data }),
+    }
+  }
+  
+  #[test]
+  fn test_from_data() {
+    let mut data = vec![0x0a, DENOM.len() as u8];
+    data.extend_from_slice(DENOM.as_bytes());
+    
+    let fungible = Fungible::from_create_denom_reply(&reply(vec![], Some(Binary(data)))).unwrap();
+    assert_eq!(fungible, Fungible::Coin(DENOM.to_string()));
+  }
+  
+  #[test]
+  fn test_from_events() {
+    let events = vec![
+      Event::new("message").add_attribute("sender", "osmo1contract"),
+      Event::new("create_denom")
+        .add_attribute("creator", "osmo1contract")
+        .add_attribute("new_token_denom", DENOM),
+    ];
+    
+    let fungible = Fungible::from_create_denom_reply(&reply(events, None)).unwrap();
+    assert_eq!(fungible, Fungible::Coin(DENOM.to_string()));
+  }
+  
+  #[test]
+  fn test_missing_denom() {
+    assert!(Fungible::from_create_denom_reply(&reply(vec![], None)).is_err());
+    assert!(Fungible::from_create_denom_reply(&reply(vec![], Some(Binary(vec![0x12, 0x01, 0x61])))).is_err());
+    assert!(Fungible::from_create_denom_reply(&reply(vec![], Some(Binary(vec![0x0a, 0x05, 0x61])))).is_err());
+    
+    let failed = Reply { id: 1, result: SubMsgResult::Err("failed".to_string()) };
+    assert!(Fungible::from_create_denom_reply(&failed).is_err());
+  }
+}
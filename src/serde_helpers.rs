@@ -0,0 +1,301 @@
+//! Helpers for `#[serde(with = "...")]` attributes and string representations of `Fungible`.
+use std::str::FromStr;
+use serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
+use crate::{Fungible, FungibleUnchecked};
+
+/// Parses the `Display` form, validating it like the object form, i.e. via `FungibleUnchecked`.
+fn parse_validated(s: &str) -> Result<Fungible, String> {
+  let unchecked = FungibleUnchecked::from_str(s)?;
+  Fungible::try_from(unchecked).map_err(|err| err.to_string())
+}
+
+/// Wraps a `Fungible` for use as a map key, serialized by its `Display` form as JSON object keys
+/// must be strings, e.g. `HashMap<FungibleStringKey, Uint128>`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct FungibleStringKey(pub Fungible);
+
+impl Serialize for FungibleStringKey {
+  fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&self.0.to_string())
+  }
+}
+
+impl<'de> Deserialize<'de> for FungibleStringKey {
+  fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+    let s = String::deserialize(deserializer)?;
+    parse_validated(&s).map(FungibleStringKey).map_err(D::Error::custom)
+  }
+}
+
+impl From<Fungible> for FungibleStringKey {
+  fn from(fungible: Fungible) -> Self {
+    FungibleStringKey(fungible)
+  }
+}
+
+impl From<FungibleStringKey> for Fungible {
+  fn from(key: FungibleStringKey) -> Self {
+    key.0
+  }
+}
+
+/// Accepts both the object form, e.g. `{"Coin":"uluna"}`, and the `Display` form, e.g.
+/// `"Coin(uluna)"`, for `#[serde(deserialize_with = "...")]` during migrations between the two.
+/// Serialization stays with whichever form the field is configured for.
+pub fn deserialize_flexible<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Fungible, D::Error> {
+  #[derive(Deserialize)]
+  #[serde(untagged)]
+  enum Flexible {
+    String(String),
+    Object(Fungible),
+  }
+  
+  match Flexible::deserialize(deserializer)? {
+    Flexible::String(s) => parse_validated(&s).map_err(D::Error::custom),
+    Flexible::Object(fungible) => Ok(fungible),
+  }
+}
+
+/// Represents an `Option<Fungible>` by its `Display` form, with `None` as `"*"` (i.e. any asset).
+pub mod as_string_optional {
+  use serde::{de::Error, Deserialize, Deserializer, Serializer};
+  use crate::Fungible;
+  
+  const ANY: &str = "*";
+  
+  pub fn serialize<S: Serializer>(value: &Option<Fungible>, serializer: S) -> Result<S::Ok, S::Error> {
+    match value {
+      Some(fungible) => serializer.serialize_str(&fungible.to_string()),
+      None => serializer.serialize_str(ANY),
+    }
+  }
+  
+  pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Fungible>, D::Error> {
+    let s = String::deserialize(deserializer)?;
+    if s == ANY {
+      Ok(None)
+    }
+    else {
+      super::parse_validated(&s).map(Some).map_err(D::Error::custom)
+    }
+  }
+}
+
+/// Represents a `Vec<Fungible>` as a single comma-joined string of `Display` forms, e.g.
+/// `"Coin(a),Token(b)"`, with the empty vector as `""`.
+pub mod list_as_string {
+  use serde::{de::Error, Deserialize, Deserializer, Serializer};
+  use crate::{Fungible, ESCAPE};
+  
+  pub fn serialize<S: Serializer>(value: &[Fungible], serializer: S) -> Result<S::Ok, S::Error> {
+    let joined = value.iter().map(Fungible::to_string).collect::<Vec<_>>().join(",");
+    serializer.serialize_str(&joined)
+  }
+  
+  pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<Fungible>, D::Error> {
+    let s = String::deserialize(deserializer)?;
+    split(&s).into_iter()
+      .map(|item| super::parse_validated(item).map_err(D::Error::custom))
+      .collect()
+  }
+  
+  /// Splits after each unescaped `)` followed by a comma. As `Display` escapes parentheses within
+  /// the identifier, these only ever close an item.
+  fn split(s: &str) -> Vec<&str> {
+    if s.is_empty() {
+      return vec![];
+    }
+    
+    let mut items = vec![];
+    let mut start = 0;
+    let mut chars = s.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+      if c == ESCAPE {
+        chars.next();
+      }
+      else if c == ')' && matches!(chars.peek(), Some((_, ','))) {
+        items.push(&s[start..=i]);
+        chars.next();
+        start = i + 2;
+      }
+    }
+    items.push(&s[start..]);
+    items
+  }
+}
+
+/// Represents a `Fungible` with the JSON of `cw20::Denom`, i.e. `{"native":"..."}` or
+/// `{"cw20":"..."}`, so fields stay interchangeable with contracts using the latter.
+pub mod as_cw20_denom {
+  use serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
+  use crate::{Fungible, FungibleUnchecked};
+  
+  #[derive(Serialize, Deserialize)]
+  #[serde(rename_all = "snake_case")]
+  enum Denom {
+    Native(String),
+    Cw20(String),
+  }
+  
+  pub fn serialize<S: Serializer>(value: &Fungible, serializer: S) -> Result<S::Ok, S::Error> {
+    match value {
+      Fungible::Coin(coin) => Denom::Native(coin.clone()),
+      Fungible::Token(token) => Denom::Cw20(token.to_string()),
+    }.serialize(serializer)
+  }
+  
+  pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Fungible, D::Error> {
+    let unchecked = match Denom::deserialize(deserializer)? {
+      Denom::Native(coin) => FungibleUnchecked::Coin(coin),
+      Denom::Cw20(token) => FungibleUnchecked::Token(token),
+    };
+    Fungible::try_from(unchecked).map_err(D::Error::custom)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use cosmwasm_std::{from_json, to_json_vec, Addr};
+  use crate::MAX_IDENTIFIER_LEN;
+  
+  #[derive(Serialize, Deserialize, Debug, PartialEq)]
+  struct Config {
+    #[serde(with = "as_string_optional")]
+    asset: Option<Fungible>,
+  }
+  
+  #[test]
+  fn test_as_string_optional() {
+    let cases = [
+      (None, r#"{"asset":"*"}"#),
+      (Some(Fungible::Coin("uluna".to_string())), r#"{"asset":"Coin(uluna)"}"#),
+      (Some(Fungible::Token(Addr::unchecked("whDAI"))), r#"{"asset":"Token(whDAI)"}"#),
+    ];
+    
+    for (asset, json) in cases {
+      let config = Config { asset };
+      assert_eq!(to_json_vec(&config).unwrap(), json.as_bytes());
+      assert_eq!(from_json::<Config>(json.as_bytes()).unwrap(), config);
+    }
+    
+    assert!(from_json::<Config>(br#"{"asset":"uluna"}"#).is_err());
+    assert!(from_json::<Config>(br#"{"asset":"Coin()"}"#).is_err());
+  }
+  
+  #[derive(Deserialize, Debug, PartialEq)]
+  struct Route {
+    #[serde(deserialize_with = "deserialize_flexible")]
+    asset: Fungible,
+  }
+  
+  #[test]
+  fn test_deserialize_flexible() {
+    let object = from_json::<Route>(br#"{"asset":{"Coin":"uluna"}}"#).unwrap();
+    let string = from_json::<Route>(br#"{"asset":"Coin(uluna)"}"#).unwrap();
+    assert_eq!(object, string);
+    assert_eq!(object.asset, Fungible::Coin("uluna".to_string()));
+    
+    let token = from_json::<Route>(br#"{"asset":"Token(whDAI)"}"#).unwrap();
+    assert_eq!(token.asset, Fungible::Token(Addr::unchecked("whDAI")));
+    
+    assert!(from_json::<Route>(br#"{"asset":"uluna"}"#).is_err());
+    assert!(from_json::<Route>(br#"{"asset":{"Coin":""}}"#).is_err());
+    assert!(from_json::<Route>(br#"{"asset":"Coin()"}"#).is_err());
+    
+    let oversized = format!(r#"{{"asset":"Coin({})"}}"#, "u".repeat(MAX_IDENTIFIER_LEN + 1));
+    assert!(from_json::<Route>(oversized.as_bytes()).is_err());
+  }
+  
+  #[derive(Serialize, Deserialize, Debug, PartialEq)]
+  struct Pair {
+    #[serde(with = "as_cw20_denom")]
+    asset: Fungible,
+  }
+  
+  #[test]
+  fn test_as_cw20_denom() {
+    let cases = [
+      (Fungible::Coin("uluna".to_string()), r#"{"asset":{"native":"uluna"}}"#),
+      (Fungible::Token(Addr::unchecked("token")), r#"{"asset":{"cw20":"token"}}"#),
+    ];
+    
+    for (asset, json) in cases {
+      let pair = from_json::<Pair>(json.as_bytes()).unwrap();
+      assert_eq!(pair, Pair { asset });
+      assert_eq!(to_json_vec(&pair).unwrap(), json.as_bytes());
+    }
+    
+    assert!(from_json::<Pair>(br#"{"asset":{"native":""}}"#).is_err());
+  }
+  
+  #[cfg(feature = "cw20")]
+  #[test]
+  fn test_as_cw20_denom_interop() {
+    use cw20::Denom;
+    
+    #[derive(Serialize, Deserialize)]
+    struct Cw20Pair {
+      asset: Denom,
+    }
+    
+    for denom in [Denom::Native("uluna".to_string()), Denom::Cw20(Addr::unchecked("token"))] {
+      let json = to_json_vec(&Cw20Pair { asset: denom.clone() }).unwrap();
+      let pair = from_json::<Pair>(&json).unwrap();
+      assert_eq!(pair.asset, Fungible::from(denom));
+      assert_eq!(to_json_vec(&pair).unwrap(), json);
+    }
+  }
+  
+  #[derive(Serialize, Deserialize, Debug, PartialEq)]
+  struct Whitelist {
+    #[serde(with = "list_as_string")]
+    assets: Vec<Fungible>,
+  }
+  
+  #[test]
+  fn test_list_as_string() {
+    let cases = [
+      (vec![], r#"{"assets":""}"#),
+      (vec![Fungible::Coin("uluna".to_string())], r#"{"assets":"Coin(uluna)"}"#),
+      (
+        vec![Fungible::Coin("a,b".to_string()), Fungible::Token(Addr::unchecked("b)")), Fungible::Coin("c),d".to_string())],
+        r#"{"assets":"Coin(a,b),Token(b\\)),Coin(c\\),d)"}"#,
+      ),
+    ];
+    
+    for (assets, json) in cases {
+      let whitelist = Whitelist { assets };
+      assert_eq!(to_json_vec(&whitelist).unwrap(), json.as_bytes());
+      assert_eq!(from_json::<Whitelist>(json.as_bytes()).unwrap(), whitelist);
+    }
+    
+    assert!(from_json::<Whitelist>(br#"{"assets":"Coin(uluna),"}"#).is_err());
+    assert!(from_json::<Whitelist>(br#"{"assets":"uluna,uusd"}"#).is_err());
+    assert!(from_json::<Whitelist>(br#"{"assets":"Coin(),Token()"}"#).is_err());
+    assert!(from_json::<Whitelist>(br#"{"assets":"Coin(uluna),Token( )"}"#).is_err());
+  }
+  
+  #[test]
+  fn test_string_key_map() {
+    use std::collections::HashMap;
+    use cosmwasm_std::Uint128;
+    
+    let key = FungibleStringKey(Fungible::Coin("uluna".to_string()));
+    let map = HashMap::from([(key.clone(), Uint128::new(100))]);
+    assert_eq!(to_json_vec(&map).unwrap(), br#"{"Coin(uluna)":"100"}"#);
+    
+    let map = HashMap::from([
+      (key, Uint128::new(100)),
+      (FungibleStringKey(Fungible::Token(Addr::unchecked("token"))), Uint128::new(200)),
+    ]);
+    let json = to_json_vec(&map).unwrap();
+    assert_eq!(from_json::<HashMap<FungibleStringKey, Uint128>>(&json).unwrap(), map);
+    
+    assert!(from_json::<HashMap<FungibleStringKey, Uint128>>(br#"{"uluna":"100"}"#).is_err());
+    assert!(from_json::<HashMap<FungibleStringKey, Uint128>>(br#"{"Token( )":"100"}"#).is_err());
+    
+    let oversized = format!(r#"{{"Coin({})":"100"}}"#, "u".repeat(MAX_IDENTIFIER_LEN + 1));
+    assert!(from_json::<HashMap<FungibleStringKey, Uint128>>(oversized.as_bytes()).is_err());
+  }
+}
@@ -0,0 +1,190 @@
+use std::{collections::{btree_set, BTreeSet}, rc::Rc};
+use schemars::JsonSchema;
+use serde::{Serialize, Deserialize};
+use crate::{Fungible, FungibleError};
+
+/// Set of `Fungible`s iterating deterministically in `Ord` order, unlike a `HashSet`.
+/// 
+/// Serializes as a sorted array, so equal sets produce identical bytes regardless of insertion
+/// order, as required e.g. for reproducible state hashes.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq, JsonSchema)]
+#[serde(transparent)]
+pub struct FungibleSet(BTreeSet<Fungible>);
+
+impl FungibleSet {
+  pub fn new() -> Self {
+    FungibleSet(BTreeSet::new())
+  }
+  
+  /// Whether `fungible` was newly inserted.
+  pub fn insert(&mut self, fungible: Fungible) -> bool {
+    self.0.insert(fungible)
+  }
+  
+  /// Whether `fungible` was present.
+  pub fn remove(&mut self, fungible: &Fungible) -> bool {
+    self.0.remove(fungible)
+  }
+  
+  pub fn contains(&self, fungible: &Fungible) -> bool {
+    self.0.contains(fungible)
+  }
+  
+  pub fn len(&self) -> usize {
+    self.0.len()
+  }
+  
+  pub fn is_empty(&self) -> bool {
+    self.0.is_empty()
+  }
+  
+  pub fn iter(&self) -> btree_set::Iter<'_, Fungible> {
+    self.0.iter()
+  }
+  
+  pub fn to_sorted_vec(&self) -> Vec<Fungible> {
+    self.0.iter().cloned().collect()
+  }
+}
+
+/// Pool sharing one allocation per distinct `Fungible`, for batch processors where the same few
+/// assets recur across thousands of items.
+#[derive(Clone, Debug, Default)]
+pub struct FungibleInterner(BTreeSet<Rc<Fungible>>);
+
+impl FungibleInterner {
+  pub fn new() -> Self {
+    FungibleInterner(BTreeSet::new())
+  }
+  
+  /// The pooled instance equal to `fungible`, pooling `fungible` itself if none yet.
+  pub fn intern(&mut self, fungible: Fungible) -> Rc<Fungible> {
+    if let Some(pooled) = self.0.get(&fungible) {
+      return pooled.clone();
+    }
+    let pooled = Rc::new(fungible);
+    self.0.insert(pooled.clone());
+    pooled
+  }
+  
+  /// Number of distinct assets pooled.
+  pub fn len(&self) -> usize {
+    self.0.len()
+  }
+  
+  pub fn is_empty(&self) -> bool {
+    self.0.is_empty()
+  }
+}
+
+impl FromIterator<Fungible> for FungibleSet {
+  fn from_iter<I: IntoIterator<Item = Fungible>>(iter: I) -> Self {
+    FungibleSet(iter.into_iter().collect())
+  }
+}
+
+impl IntoIterator for FungibleSet {
+  type Item = Fungible;
+  type IntoIter = btree_set::IntoIter<Fungible>;
+  
+  fn into_iter(self) -> Self::IntoIter {
+    self.0.into_iter()
+  }
+}
+
+impl<'a> IntoIterator for &'a FungibleSet {
+  type Item = &'a Fungible;
+  type IntoIter = btree_set::Iter<'a, Fungible>;
+  
+  fn into_iter(self) -> Self::IntoIter {
+    self.0.iter()
+  }
+}
+
+/// Dedups a vector which is already sorted by `Ord`, which is cheaper than re-sorting. Errors if it
+/// is not actually sorted, leaving it untouched.
+pub fn dedup_sorted(v: &mut Vec<Fungible>) -> Result<(), FungibleError> {
+  if let Some(index) = v.windows(2).position(|pair| pair[0] > pair[1]) {
+    return Err(FungibleError::NotSorted { index: index + 1 });
+  }
+  v.dedup();
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use cosmwasm_std::{from_json, to_json_vec, Addr};
+  
+  #[test]
+  fn test_deterministic_serialization() {
+    let assets = [
+      Fungible::Coin("uusd".to_string()),
+      Fungible::Token(Addr::unchecked("b")),
+      Fungible::Coin("uluna".to_string()),
+      Fungible::Token(Addr::unchecked("a")),
+    ];
+    
+    let mut forward = FungibleSet::new();
+    for asset in assets.iter() {
+      forward.insert(asset.clone());
+    }
+    let backward: FungibleSet = assets.iter().rev().cloned().collect();
+    
+    let bytes = to_json_vec(&forward).unwrap();
+    assert_eq!(bytes, to_json_vec(&backward).unwrap());
+    assert_eq!(bytes, br#"[{"Token":"a"},{"Token":"b"},{"Coin":"uluna"},{"Coin":"uusd"}]"#);
+    assert_eq!(from_json::<FungibleSet>(&bytes).unwrap(), forward);
+    
+    let mut sorted = assets.to_vec();
+    sorted.sort();
+    assert_eq!(forward.to_sorted_vec(), sorted);
+  }
+  
+  #[test]
+  fn test_deserialize_unsorted() {
+    let set = from_json::<FungibleSet>(br#"[{"Coin":"uusd"},{"Coin":"uluna"},{"Coin":"uusd"}]"#).unwrap();
+    assert_eq!(set.len(), 2);
+    assert_eq!(to_json_vec(&set).unwrap(), br#"[{"Coin":"uluna"},{"Coin":"uusd"}]"#);
+  }
+  
+  #[test]
+  fn test_interner() {
+    let mut interner = FungibleInterner::new();
+    let first = interner.intern(Fungible::Coin("uluna".to_string()));
+    
+    for _ in 0..1000 {
+      let pooled = interner.intern(Fungible::Coin("uluna".to_string()));
+      assert!(Rc::ptr_eq(&pooled, &first));
+    }
+    
+    let token = interner.intern(Fungible::Token(Addr::unchecked("uluna")));
+    assert!(!Rc::ptr_eq(&token, &first));
+    assert_eq!(interner.len(), 2);
+    assert_eq!(Rc::strong_count(&first), 2);
+  }
+  
+  #[test]
+  fn test_dedup_sorted() {
+    let luna = Fungible::Coin("uluna".to_string());
+    let token = Fungible::Token(Addr::unchecked("token"));
+    
+    let mut assets = vec![token.clone(), token.clone(), luna.clone(), luna.clone(), luna.clone()];
+    dedup_sorted(&mut assets).unwrap();
+    assert_eq!(assets, vec![token.clone(), luna.clone()]);
+    
+    let mut empty = vec![];
+    dedup_sorted(&mut empty).unwrap();
+    assert!(empty.is_empty());
+  }
+  
+  #[test]
+  fn test_dedup_unsorted() {
+    let luna = Fungible::Coin("uluna".to_string());
+    let token = Fungible::Token(Addr::unchecked("token"));
+    
+    let mut assets = vec![token.clone(), luna.clone(), luna.clone(), token.clone()];
+    assert_eq!(dedup_sorted(&mut assets), Err(FungibleError::NotSorted { index: 3 }));
+    assert_eq!(assets.len(), 4);
+  }
+}
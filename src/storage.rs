@@ -0,0 +1,457 @@
+use cosmwasm_std::{Addr, Order, StdError, StdResult, Storage, Uint128};
+use cw_storage_plus::{Bound, KeyDeserialize, Map, PrimaryKey};
+use serde::{de::DeserializeOwned, Serialize};
+use crate::{Fungible, FungibleKind, COIN_TAG, TOKEN_TAG};
+
+impl Fungible {
+  /// Errors if this asset has no entry in `registry`.
+  pub fn assert_registered<V>(&self, store: &dyn Storage, registry: &Map<Fungible, V>) -> StdResult<()>
+  where V: Serialize + DeserializeOwned
+  {
+    match registry.may_load(store, self.clone())? {
+      Some(_) => Ok(()),
+      None => Err(StdError::generic_err(format!("Unregistered asset: {}", self))),
+    }
+  }
+  
+  /// Loads the metadata of this asset from the allowlist `map`, erroring if it is not supported.
+  pub fn load_metadata<M>(&self, store: &dyn Storage, map: &Map<Fungible, M>) -> StdResult<M>
+  where M: Serialize + DeserializeOwned
+  {
+    map.may_load(store, self.clone())?
+      .ok_or_else(|| StdError::generic_err(format!("Asset not supported: {}", self)))
+  }
+}
+
+/// Classifies `s` as a `Token` if registered as such in `registry`, and as a `Coin` otherwise. More
+/// reliable than guessing by address validation, as address-like denoms stay coins.
+pub fn classify_with_registry<V>(s: &str, registry: &Map<Fungible, V>, store: &dyn Storage) -> StdResult<Fungible>
+where V: Serialize + DeserializeOwned
+{
+  let token = Fungible::Token(Addr::unchecked(s));
+  if registry.has(store, token.clone()) {
+    Ok(token)
+  }
+  else {
+    Ok(Fungible::Coin(s.to_string()))
+  }
+}
+
+/// Adds `amount` to the balance of `key`, defaulting to zero, and returns the new balance.
+/// Errors on overflow.
+pub fn credit(map: &Map<Fungible, Uint128>, store: &mut dyn Storage, key: &Fungible, amount: Uint128) -> StdResult<Uint128> {
+  let balance = map.may_load(store, key.clone())?.unwrap_or_default().checked_add(amount)?;
+  map.save(store, key.clone(), &balance)?;
+  Ok(balance)
+}
+
+/// Subtracts `amount` from the balance of `key`, defaulting to zero, and returns the new balance.
+/// Errors on insufficient balance.
+pub fn debit(map: &Map<Fungible, Uint128>, store: &mut dyn Storage, key: &Fungible, amount: Uint128) -> StdResult<Uint128> {
+  let balance = map.may_load(store, key.clone())?.unwrap_or_default();
+  let balance = balance.checked_sub(amount)
+    .map_err(|_| StdError::generic_err(format!("Insufficient balance of {}: {} < {}", key, balance, amount)))?;
+  map.save(store, key.clone(), &balance)?;
+  Ok(balance)
+}
+
+/// Removes `key` from both a balance map and its index of active assets. Removing a missing key
+/// is a no-op.
+pub fn remove_and_deindex(
+  balances: &Map<Fungible, Uint128>,
+  index: &Map<Fungible, ()>,
+  store: &mut dyn Storage,
+  key: &Fungible,
+) -> StdResult<()> {
+  balances.remove(store, key.clone());
+  index.remove(store, key.clone());
+  Ok(())
+}
+
+/// Sets the reentrancy flag of `key`, erroring if already locked.
+pub fn lock(map: &Map<Fungible, bool>, store: &mut dyn Storage, key: &Fungible) -> StdResult<()> {
+  if map.may_load(store, key.clone())?.unwrap_or(false) {
+    return Err(StdError::generic_err(format!("Asset locked: {}", key)));
+  }
+  map.save(store, key.clone(), &true)
+}
+
+/// Clears the reentrancy flag of `key`. Unlocking an unlocked asset is a no-op.
+pub fn unlock(map: &Map<Fungible, bool>, store: &mut dyn Storage, key: &Fungible) {
+  map.remove(store, key.clone());
+}
+
+/// Smallest key of `map`, which matches the `Ord` of `Fungible`.
+pub fn first_key<V>(map: &Map<Fungible, V>, store: &dyn Storage) -> StdResult<Option<Fungible>>
+where V: Serialize + DeserializeOwned
+{
+  map.keys(store, None, None, Order::Ascending).next().transpose()
+}
+
+/// Largest key of `map`, which matches the `Ord` of `Fungible`.
+pub fn last_key<V>(map: &Map<Fungible, V>, store: &dyn Storage) -> StdResult<Option<Fungible>>
+where V: Serialize + DeserializeOwned
+{
+  map.keys(store, None, None, Order::Descending).next().transpose()
+}
+
+/// Storage discriminant of a `kind`, i.e. the prefix of its entries.
+fn kind_tag(kind: FungibleKind) -> u8 {
+  match kind {
+    FungibleKind::Coin => COIN_TAG,
+    FungibleKind::Token => TOKEN_TAG,
+  }
+}
+
+/// Number of distinct assets in `map`, without deserializing the values.
+pub fn count_assets<V>(map: &Map<Fungible, V>, store: &dyn Storage) -> StdResult<u64>
+where V: Serialize + DeserializeOwned
+{
+  Ok(map.keys_raw(store, None, None, Order::Ascending).count() as u64)
+}
+
+/// Sum of the amounts of the given kind in `map`. Errors on overflow.
+pub fn sum_kind(map: &Map<Fungible, Uint128>, store: &dyn Storage, kind: FungibleKind) -> StdResult<Uint128> {
+  map.prefix(kind_tag(kind))
+    .range_raw(store, None, None, Order::Ascending)
+    .try_fold(Uint128::zero(), |total, entry| Ok(total.checked_add(entry?.1)?))
+}
+
+/// Keys of `map` of the given kind, in ascending order, without deserializing the values.
+pub fn keys_of_kind<V>(map: &Map<Fungible, V>, store: &dyn Storage, kind: FungibleKind) -> StdResult<Vec<Fungible>>
+where V: Serialize + DeserializeOwned
+{
+  map.prefix(kind_tag(kind))
+    .keys(store, None, None, Order::Ascending)
+    .map(|key| key.map(|identifier| Fungible::from_parts(identifier, kind)))
+    .collect()
+}
+
+/// Removes every entry of the given kind from `map`, returning the number of entries removed.
+pub fn clear_kind<V>(map: &Map<Fungible, V>, store: &mut dyn Storage, kind: FungibleKind) -> StdResult<u64>
+where V: Serialize + DeserializeOwned
+{
+  // collect first, as the store cannot be mutated while iterating
+  let keys = keys_of_kind(map, store, kind)?;
+  for key in &keys {
+    map.remove(store, key.clone());
+  }
+  Ok(keys.len() as u64)
+}
+
+/// Canonical key of the unordered pair `{a, b}`, sorted by `Ord`, such that e.g. a pool stored in a
+/// `Map<(Fungible, Fungible), Pool>` under `pair_key(a, b)` is found under `pair_key(b, a)` as well.
+pub fn pair_key(a: &Fungible, b: &Fungible) -> (Fungible, Fungible) {
+  if a <= b {
+    (a.clone(), b.clone())
+  }
+  else {
+    (b.clone(), a.clone())
+  }
+}
+
+/// Decoded entry of a map keyed by `(Fungible, K)`.
+pub type AssetKeyedRecord<K, V> = ((Fungible, K), V);
+
+/// Entries of a map keyed by `(Fungible, K)`, e.g. pools under `pair_key`, in the given order.
+/// cw-storage-plus decodes the first element of a tuple key from a single segment, whereas a
+/// `Fungible` spans two, so a plain `range` over such a map fails to decode its keys.
+pub fn range_by_asset<'a, K, V>(map: &Map<'a, (Fungible, K), V>, store: &dyn Storage, order: Order) -> StdResult<Vec<AssetKeyedRecord<K::Output, V>>>
+where
+  K: PrimaryKey<'a> + KeyDeserialize,
+  V: Serialize + DeserializeOwned,
+{
+  map.range_raw(store, None, None, order)
+    .map(|entry| {
+      let (key, value) = entry?;
+      let (fungible, rest) = Fungible::split_key_prefix(&key)?;
+      Ok(((fungible, K::from_slice(rest)?), value))
+    })
+    .collect()
+}
+
+/// Coin entries of `map` with a denom in `[start, end)`, in ascending order. Tokens are skipped.
+pub fn coins_in_range<V>(map: &Map<Fungible, V>, store: &dyn Storage, start: Option<&str>, end: Option<&str>) -> StdResult<Vec<(Fungible, V)>>
+where V: Serialize + DeserializeOwned
+{
+  map.prefix(COIN_TAG)
+    .range(store, start.map(Bound::inclusive), end.map(Bound::exclusive), Order::Ascending)
+    .map(|entry| entry.map(|(denom, value)| (Fungible::Coin(denom), value)))
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use cosmwasm_std::testing::MockStorage;
+  
+  #[test]
+  fn test_keys_of_kind() {
+    let mut store = MockStorage::new();
+    let map = Map::<Fungible, Uint128>::new("balances");
+    let tokens = vec![Fungible::Token(Addr::unchecked("token1")), Fungible::Token(Addr::unchecked("token2"))];
+    let coins = vec![Fungible::Coin("uluna".to_string()), Fungible::Coin("uusd".to_string())];
+    
+    for key in [&coins[1], &tokens[1], &coins[0], &tokens[0]] {
+      map.save(&mut store, key.clone(), &Uint128::new(1)).unwrap();
+    }
+    
+    assert_eq!(keys_of_kind(&map, &store, FungibleKind::Token).unwrap(), tokens);
+    assert_eq!(keys_of_kind(&map, &store, FungibleKind::Coin).unwrap(), coins);
+    assert!(keys_of_kind(&Map::<Fungible, ()>::new("empty"), &store, FungibleKind::Coin).unwrap().is_empty());
+  }
+  
+  #[test]
+  fn test_count_and_sum() {
+    let mut store = MockStorage::new();
+    let map = Map::<Fungible, Uint128>::new("balances");
+    
+    assert_eq!(count_assets(&map, &store).unwrap(), 0);
+    assert_eq!(sum_kind(&map, &store, FungibleKind::Coin).unwrap(), Uint128::zero());
+    
+    map.save(&mut store, Fungible::Coin("uluna".to_string()), &Uint128::new(100)).unwrap();
+    map.save(&mut store, Fungible::Coin("uusd".to_string()), &Uint128::new(200)).unwrap();
+    map.save(&mut store, Fungible::Token(Addr::unchecked("token")), &Uint128::new(400)).unwrap();
+    map.save(&mut store, Fungible::Coin("uluna".to_string()), &Uint128::new(300)).unwrap();
+    
+    assert_eq!(count_assets(&map, &store).unwrap(), 3);
+    assert_eq!(sum_kind(&map, &store, FungibleKind::Coin).unwrap(), Uint128::new(500));
+    assert_eq!(sum_kind(&map, &store, FungibleKind::Token).unwrap(), Uint128::new(400));
+    
+    map.save(&mut store, Fungible::Token(Addr::unchecked("other")), &Uint128::MAX).unwrap();
+    assert!(sum_kind(&map, &store, FungibleKind::Token).is_err());
+  }
+  
+  #[test]
+  fn test_clear_kind() {
+    let mut store = MockStorage::new();
+    let map = Map::<Fungible, Uint128>::new("balances");
+    let token = Fungible::Token(Addr::unchecked("token"));
+    
+    for key in [Fungible::Coin("uluna".to_string()), token.clone(), Fungible::Coin("uusd".to_string())] {
+      map.save(&mut store, key, &Uint128::new(1)).unwrap();
+    }
+    
+    assert_eq!(clear_kind(&map, &mut store, FungibleKind::Coin).unwrap(), 2);
+    assert_eq!(map.keys(&store, None, None, Order::Ascending).collect::<StdResult<Vec<_>>>().unwrap(), vec![token]);
+    assert_eq!(clear_kind(&map, &mut store, FungibleKind::Coin).unwrap(), 0);
+  }
+  
+  #[test]
+  fn test_pair_key() {
+    #[derive(Serialize, serde::Deserialize, Debug, PartialEq)]
+    struct Pool {
+      reserves: (Uint128, Uint128),
+    }
+    
+    let mut store = MockStorage::new();
+    let pools = Map::<(Fungible, Fungible), Pool>::new("pools");
+    let luna = Fungible::Coin("uluna".to_string());
+    let usd = Fungible::Coin("uusd".to_string());
+    let token = Fungible::Token(Addr::unchecked("token"));
+    
+    assert_eq!(pair_key(&luna, &token), (token.clone(), luna.clone()));
+    assert_eq!(pair_key(&token, &luna), (token.clone(), luna.clone()));
+    
+    let pool = Pool { reserves: (Uint128::new(100), Uint128::new(200)) };
+    pools.save(&mut store, pair_key(&luna, &token), &pool).unwrap();
+    assert_eq!(pools.load(&store, pair_key(&token, &luna)).unwrap(), pool);
+    assert!(pools.may_load(&store, pair_key(&luna, &usd)).unwrap().is_none());
+    
+    // pools of the smaller asset
+    pools.save(&mut store, pair_key(&usd, &token), &pool).unwrap();
+    let pairs = pools.prefix(token).keys(&store, None, None, Order::Ascending).collect::<StdResult<Vec<_>>>().unwrap();
+    assert_eq!(pairs, vec![luna, usd]);
+  }
+  
+  #[test]
+  fn test_pair_key_listing() {
+    let mut store = MockStorage::new();
+    let pools = Map::<(Fungible, Fungible), u8>::new("pools");
+    let luna = Fungible::Coin("uluna".to_string());
+    let usd = Fungible::Coin("uusd".to_string());
+    let token = Fungible::Token(Addr::unchecked("token"));
+    
+    pools.save(&mut store, pair_key(&usd, &luna), &1).unwrap();
+    pools.save(&mut store, pair_key(&luna, &token), &2).unwrap();
+    pools.save(&mut store, pair_key(&usd, &token), &3).unwrap();
+    
+    let entries = range_by_asset(&pools, &store, Order::Ascending).unwrap();
+    assert_eq!(entries, vec![
+      ((token.clone(), luna.clone()), 2),
+      ((token.clone(), usd.clone()), 3),
+      ((luna.clone(), usd.clone()), 1),
+    ]);
+    
+    let keys = range_by_asset(&pools, &store, Order::Descending).unwrap().into_iter().map(|(key, _)| key).collect::<Vec<_>>();
+    assert_eq!(keys, vec![pair_key(&luna, &usd), pair_key(&usd, &token), pair_key(&luna, &token)]);
+  }
+  
+  #[test]
+  fn test_classify_with_registry() {
+    let mut store = MockStorage::new();
+    let registry = Map::<Fungible, ()>::new("registry");
+    registry.save(&mut store, Fungible::Token(Addr::unchecked("terra1token")), &()).unwrap();
+    registry.save(&mut store, Fungible::Coin("terra1coin".to_string()), &()).unwrap();
+    
+    assert_eq!(classify_with_registry("terra1token", &registry, &store).unwrap(), Fungible::Token(Addr::unchecked("terra1token")));
+    assert_eq!(classify_with_registry("uluna", &registry, &store).unwrap(), Fungible::Coin("uluna".to_string()));
+    assert_eq!(classify_with_registry("terra1other", &registry, &store).unwrap(), Fungible::Coin("terra1other".to_string()));
+    assert_eq!(classify_with_registry("terra1coin", &registry, &store).unwrap(), Fungible::Coin("terra1coin".to_string()));
+  }
+  
+  #[test]
+  fn test_load_metadata() {
+    #[derive(Serialize, serde::Deserialize, Debug, PartialEq)]
+    struct Metadata {
+      decimals: u8,
+    }
+    
+    let mut store = MockStorage::new();
+    let supported = Map::<Fungible, Metadata>::new("supported");
+    let luna = Fungible::Coin("uluna".to_string());
+    supported.save(&mut store, luna.clone(), &Metadata { decimals: 6 }).unwrap();
+    
+    assert_eq!(luna.load_metadata(&store, &supported).unwrap(), Metadata { decimals: 6 });
+    assert_eq!(
+      Fungible::Token(Addr::unchecked("token")).load_metadata(&store, &supported).unwrap_err(),
+      StdError::generic_err("Asset not supported: Token(token)"),
+    );
+  }
+  
+  #[test]
+  fn test_assert_registered() {
+    let mut store = MockStorage::new();
+    let registry = Map::<Fungible, ()>::new("registry");
+    let coin = Fungible::Coin("uluna".to_string());
+    let token = Fungible::Token(Addr::unchecked("token"));
+    
+    registry.save(&mut store, coin.clone(), &()).unwrap();
+    
+    assert!(coin.assert_registered(&store, &registry).is_ok());
+    assert_eq!(
+      token.assert_registered(&store, &registry).unwrap_err(),
+      StdError::generic_err("Unregistered asset: Token(token)"),
+    );
+  }
+  
+  #[test]
+  fn test_credit_debit() {
+    let mut store = MockStorage::new();
+    let balances = Map::<Fungible, Uint128>::new("balances");
+    let coin = Fungible::Coin("uluna".to_string());
+    
+    assert_eq!(credit(&balances, &mut store, &coin, Uint128::new(100)).unwrap(), Uint128::new(100));
+    assert_eq!(credit(&balances, &mut store, &coin, Uint128::new(50)).unwrap(), Uint128::new(150));
+    assert_eq!(debit(&balances, &mut store, &coin, Uint128::new(120)).unwrap(), Uint128::new(30));
+    assert_eq!(balances.load(&store, coin).unwrap(), Uint128::new(30));
+  }
+  
+  #[test]
+  fn test_debit_insufficient() {
+    let mut store = MockStorage::new();
+    let balances = Map::<Fungible, Uint128>::new("balances");
+    let coin = Fungible::Coin("uluna".to_string());
+    
+    credit(&balances, &mut store, &coin, Uint128::new(100)).unwrap();
+    assert_eq!(
+      debit(&balances, &mut store, &coin, Uint128::new(101)).unwrap_err(),
+      StdError::generic_err("Insufficient balance of Coin(uluna): 100 < 101"),
+    );
+    assert_eq!(balances.load(&store, coin).unwrap(), Uint128::new(100));
+    
+    let missing = Fungible::Coin("uusd".to_string());
+    assert!(debit(&balances, &mut store, &missing, Uint128::new(1)).is_err());
+  }
+  
+  #[test]
+  fn test_credit_overflow() {
+    let mut store = MockStorage::new();
+    let balances = Map::<Fungible, Uint128>::new("balances");
+    let coin = Fungible::Coin("uluna".to_string());
+    
+    credit(&balances, &mut store, &coin, Uint128::MAX).unwrap();
+    assert!(credit(&balances, &mut store, &coin, Uint128::new(1)).is_err());
+  }
+  
+  #[test]
+  fn test_remove_and_deindex() {
+    let mut store = MockStorage::new();
+    let balances = Map::<Fungible, Uint128>::new("balances");
+    let index = Map::<Fungible, ()>::new("index");
+    let coin = Fungible::Coin("uluna".to_string());
+    
+    balances.save(&mut store, coin.clone(), &Uint128::new(100)).unwrap();
+    index.save(&mut store, coin.clone(), &()).unwrap();
+    
+    remove_and_deindex(&balances, &index, &mut store, &coin).unwrap();
+    assert!(!balances.has(&store, coin.clone()));
+    assert!(!index.has(&store, coin.clone()));
+    
+    // missing key is a no-op
+    remove_and_deindex(&balances, &index, &mut store, &coin).unwrap();
+  }
+  
+  #[test]
+  fn test_lock() {
+    let mut store = MockStorage::new();
+    let locks = Map::<Fungible, bool>::new("locks");
+    let coin = Fungible::Coin("uluna".to_string());
+    let token = Fungible::Token(Addr::unchecked("token"));
+    
+    lock(&locks, &mut store, &coin).unwrap();
+    assert_eq!(lock(&locks, &mut store, &coin).unwrap_err(), StdError::generic_err("Asset locked: Coin(uluna)"));
+    lock(&locks, &mut store, &token).unwrap();
+    
+    unlock(&locks, &mut store, &coin);
+    assert_eq!(locks.may_load(&store, coin.clone()).unwrap(), None);
+    lock(&locks, &mut store, &coin).unwrap();
+    assert!(lock(&locks, &mut store, &token).is_err());
+  }
+  
+  #[test]
+  fn test_first_last_key() {
+    let mut store = MockStorage::new();
+    let map = Map::<Fungible, u8>::new("test");
+    
+    assert_eq!(first_key(&map, &store).unwrap(), None);
+    assert_eq!(last_key(&map, &store).unwrap(), None);
+    
+    let mut assets = vec![
+      Fungible::Coin("uusd".to_string()),
+      Fungible::Token(Addr::unchecked("token2")),
+      Fungible::Coin("uluna".to_string()),
+      Fungible::Token(Addr::unchecked("token1")),
+    ];
+    for asset in &assets {
+      map.save(&mut store, asset.clone(), &0).unwrap();
+    }
+    assets.sort();
+    
+    assert_eq!(first_key(&map, &store).unwrap().as_ref(), assets.first());
+    assert_eq!(last_key(&map, &store).unwrap().as_ref(), assets.last());
+    assert_eq!(first_key(&map, &store).unwrap(), Some(Fungible::Token(Addr::unchecked("token1"))));
+    assert_eq!(last_key(&map, &store).unwrap(), Some(Fungible::Coin("uusd".to_string())));
+  }
+  
+  #[test]
+  fn test_coins_in_range() {
+    let mut store = MockStorage::new();
+    let map = Map::<Fungible, u8>::new("test");
+    
+    for (i, denom) in ["uatom", "ukrw", "uluna", "uosmo", "uusd"].into_iter().enumerate() {
+      map.save(&mut store, Fungible::Coin(denom.to_string()), &(i as u8)).unwrap();
+    }
+    map.save(&mut store, Fungible::Token(Addr::unchecked("ulx")), &10).unwrap();
+    
+    assert_eq!(coins_in_range(&map, &store, Some("ukrw"), Some("uosmo")).unwrap(), vec![
+      (Fungible::Coin("ukrw".to_string()), 1),
+      (Fungible::Coin("uluna".to_string()), 2),
+    ]);
+    assert_eq!(coins_in_range(&map, &store, Some("uo"), None).unwrap(), vec![
+      (Fungible::Coin("uosmo".to_string()), 3),
+      (Fungible::Coin("uusd".to_string()), 4),
+    ]);
+    assert_eq!(coins_in_range(&map, &store, None, None).unwrap().len(), 5);
+  }
+}
@@ -0,0 +1,38 @@
+//! Shared fixtures for tests running against a deployed `cw20-base` in `cw-multi-test`.
+
+use cosmwasm_std::{Addr, Uint128};
+use cw20::{Cw20Coin, MinterResponse};
+use cw_multi_test::{App, ContractWrapper, Executor};
+
+/// Deploys a `cw20-base` token with the given initial balances and optional minter.
+pub fn deploy_cw20(app: &mut App, balances: &[(&str, u128)], minter: Option<&str>) -> Addr {
+  let code_id = app.store_code(Box::new(ContractWrapper::new(
+    cw20_base::contract::execute,
+    cw20_base::contract::instantiate,
+    cw20_base::contract::query,
+  )));
+  
+  app.instantiate_contract(
+    code_id,
+    Addr::unchecked("creator"),
+    &cw20_base::msg::InstantiateMsg {
+      name: "Test Token".to_string(),
+      symbol: "TEST".to_string(),
+      decimals: 6,
+      initial_balances: balances.iter()
+        .map(|(address, amount)| Cw20Coin {
+          address: address.to_string(),
+          amount: Uint128::new(*amount),
+        })
+        .collect(),
+      mint: minter.map(|minter| MinterResponse {
+        minter: minter.to_string(),
+        cap: None,
+      }),
+      marketing: None,
+    },
+    &[],
+    "token",
+    None,
+  ).unwrap()
+}
@@ -0,0 +1,290 @@
+use std::str::FromStr;
+use cosmwasm_std::{Addr, Api, StdResult};
+use schemars::JsonSchema;
+use serde::{Serialize, Deserialize};
+use crate::{Fungible, FungibleError};
+
+/// Unvalidated counterpart of `Fungible` as received in messages.
+/// 
+/// Shares its JSON representation with `Fungible`, so clients need not care which of the two a
+/// contract uses. A `Fungible` is expected to only ever be built from a validated `FungibleUnchecked`
+/// (or from trusted state), so that its `Token` address is known to be valid.
+/// 
+/// Deserialization, of `Fungible` as well, also accepts the historical and `cw20::Denom` spellings
+/// of the tags, so a migration of the tag format cannot render stored values unreadable. Only the
+/// canonical `Coin`/`Token` is ever serialized.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub enum FungibleUnchecked {
+  #[serde(alias = "coin", alias = "native")]
+  Coin(String),
+  #[serde(alias = "token", alias = "cw20")]
+  Token(String),
+}
+
+impl Fungible {
+  /// Whether the identifier is neither empty nor whitespace-only.
+  pub fn is_usable(&self) -> bool {
+    !self.inner().trim().is_empty()
+  }
+  
+  /// Errors unless `is_usable`, e.g. at entry points before the asset touches storage or messages.
+  pub fn assert_usable(&self) -> StdResult<()> {
+    if !self.is_usable() {
+      return Err(FungibleError::EmptyIdentifier { kind: self.kind() }.into());
+    }
+    Ok(())
+  }
+}
+
+impl FungibleUnchecked {
+  /// Rejects empty, whitespace-only and oversized identifiers like deserializing a `Fungible`, then
+  /// validates the token address, if any, against `api`.
+  pub fn validate(&self, api: &dyn Api) -> StdResult<Fungible> {
+    match Fungible::try_from(self.clone())? {
+      Fungible::Coin(coin) => Ok(Fungible::Coin(coin)),
+      Fungible::Token(token) => Ok(Fungible::Token(api.addr_validate(token.as_str())?)),
+    }
+  }
+}
+
+/// Validates every item like `FungibleUnchecked::validate`, but reports all failures as
+/// `(index, error)` instead of stopping at the first.
+pub fn validate_all(api: &dyn Api, items: &[FungibleUnchecked]) -> Result<Vec<Fungible>, Vec<(usize, FungibleError)>> {
+  let mut valid = Vec::with_capacity(items.len());
+  let mut errors = vec![];
+  
+  for (index, item) in items.iter().enumerate() {
+    let result = Fungible::try_from(item.clone()).and_then(|fungible| match fungible {
+      Fungible::Coin(_) => Ok(fungible),
+      Fungible::Token(token) => api.addr_validate(token.as_str())
+        .map(Fungible::Token)
+        .map_err(|_| FungibleError::InvalidAddress { address: token.into_string() }),
+    });
+    
+    match result {
+      Ok(fungible) => valid.push(fungible),
+      Err(err) => errors.push((index, err)),
+    }
+  }
+  
+  if errors.is_empty() {
+    Ok(valid)
+  }
+  else {
+    Err(errors)
+  }
+}
+
+/// Maximum length in bytes of the identifier of a deserialized `Fungible`, guarding contracts keying
+/// storage by `Fungible` against oversized denoms.
+pub const MAX_IDENTIFIER_LEN: usize = 256;
+
+/// Used to deserialize `Fungible`, rejecting empty or whitespace-only identifiers as well as those
+/// longer than `MAX_IDENTIFIER_LEN` at the message boundary. Token addresses are not validated, as
+/// deserialized state is trusted.
+impl TryFrom<FungibleUnchecked> for Fungible {
+  type Error = FungibleError;
+  
+  fn try_from(unchecked: FungibleUnchecked) -> Result<Self, Self::Error> {
+    let fungible = match unchecked {
+      FungibleUnchecked::Coin(coin) => Fungible::Coin(coin),
+      FungibleUnchecked::Token(token) => Fungible::Token(Addr::unchecked(token)),
+    };
+    
+    if !fungible.is_usable() {
+      return Err(FungibleError::EmptyIdentifier { kind: fungible.kind() });
+    }
+    if fungible.inner().len() > MAX_IDENTIFIER_LEN {
+      return Err(FungibleError::IdentifierTooLong { kind: fungible.kind(), len: fungible.inner().len(), max: MAX_IDENTIFIER_LEN });
+    }
+    Ok(fungible)
+  }
+}
+
+impl From<Fungible> for FungibleUnchecked {
+  fn from(fungible: Fungible) -> Self {
+    match fungible {
+      Fungible::Coin(coin) => FungibleUnchecked::Coin(coin),
+      Fungible::Token(token) => FungibleUnchecked::Token(token.into_string()),
+    }
+  }
+}
+
+/// Parses the `Display` form of `Fungible`, e.g. from untrusted input, deferring address validation
+/// to `validate`.
+impl FromStr for FungibleUnchecked {
+  type Err = String;
+  
+  fn from_str(s: &str) -> crate::Result<Self> {
+    Fungible::from_str(s).map(FungibleUnchecked::from)
+  }
+}
+
+impl<'a> From<&'a Fungible> for FungibleUnchecked {
+  fn from(fungible: &'a Fungible) -> Self {
+    fungible.clone().into()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use cosmwasm_std::{from_json, to_json_vec};
+  use cosmwasm_std::testing::MockApi;
+  
+  #[test]
+  fn test_serialization() {
+    let coin = Fungible::Coin("uluna".to_string());
+    let token = Fungible::Token(Addr::unchecked("token"));
+    
+    assert_eq!(to_json_vec(&coin).unwrap(), to_json_vec(&FungibleUnchecked::from(&coin)).unwrap());
+    assert_eq!(to_json_vec(&token).unwrap(), to_json_vec(&FungibleUnchecked::from(&token)).unwrap());
+    
+    let unchecked: FungibleUnchecked = from_json(to_json_vec(&token).unwrap()).unwrap();
+    assert_eq!(unchecked, FungibleUnchecked::Token("token".to_string()));
+  }
+  
+  #[test]
+  fn test_tag_aliases() {
+    let coin = Fungible::Coin("uluna".to_string());
+    let token = Fungible::Token(Addr::unchecked("token"));
+    
+    for tag in ["Coin", "coin", "native"] {
+      let json = format!(r#"{{"{}":"uluna"}}"#, tag);
+      assert_eq!(from_json::<Fungible>(json.as_bytes()).unwrap(), coin);
+      assert_eq!(from_json::<FungibleUnchecked>(json.as_bytes()).unwrap(), FungibleUnchecked::Coin("uluna".to_string()));
+    }
+    for tag in ["Token", "token", "cw20"] {
+      let json = format!(r#"{{"{}":"token"}}"#, tag);
+      assert_eq!(from_json::<Fungible>(json.as_bytes()).unwrap(), token);
+    }
+    
+    assert_eq!(to_json_vec(&coin).unwrap(), br#"{"Coin":"uluna"}"#);
+    assert_eq!(to_json_vec(&token).unwrap(), br#"{"Token":"token"}"#);
+    assert!(from_json::<Fungible>(br#"{"COIN":"uluna"}"#).is_err());
+  }
+  
+  #[test]
+  fn test_parse_unchecked() {
+    let api = MockApi::default();
+    
+    let unchecked = FungibleUnchecked::from_str("Token(INVALID)").unwrap();
+    assert_eq!(unchecked, FungibleUnchecked::Token("INVALID".to_string()));
+    assert!(unchecked.validate(&api).is_err());
+    
+    let unchecked = FungibleUnchecked::from_str("Token(token)").unwrap();
+    assert_eq!(unchecked.validate(&api).unwrap(), Fungible::Token(Addr::unchecked("token")));
+    
+    let unchecked = FungibleUnchecked::from_str("Coin(a\\(b)").unwrap();
+    assert_eq!(unchecked, FungibleUnchecked::Coin("a(b".to_string()));
+    assert!(FungibleUnchecked::from_str("uluna").is_err());
+  }
+  
+  #[test]
+  fn test_validate() {
+    let api = MockApi::default();
+    
+    assert_eq!(FungibleUnchecked::Coin("uluna".to_string()).validate(&api).unwrap(), Fungible::Coin("uluna".to_string()));
+    assert_eq!(FungibleUnchecked::Token("token".to_string()).validate(&api).unwrap(), Fungible::Token(Addr::unchecked("token")));
+    assert!(FungibleUnchecked::Token("INVALID".to_string()).validate(&api).is_err());
+  }
+  
+  #[test]
+  fn test_validate_unusable() {
+    use cosmwasm_std::StdError;
+    
+    let api = MockApi::default();
+    
+    assert_eq!(
+      FungibleUnchecked::Coin("".to_string()).validate(&api).unwrap_err(),
+      StdError::from(FungibleError::EmptyIdentifier { kind: crate::FungibleKind::Coin }),
+    );
+    assert!(FungibleUnchecked::Token(" ".to_string()).validate(&api).is_err());
+    assert!(FungibleUnchecked::Coin("u".repeat(MAX_IDENTIFIER_LEN + 1)).validate(&api).is_err());
+  }
+  
+  #[test]
+  fn test_usable() {
+    use cosmwasm_std::StdError;
+    
+    for inner in ["", " ", "\t\n"] {
+      let coin = Fungible::Coin(inner.to_string());
+      let token = Fungible::Token(Addr::unchecked(inner));
+      assert!(!coin.is_usable());
+      assert!(!token.is_usable());
+      assert_eq!(coin.assert_usable().unwrap_err(), StdError::generic_err("Empty Coin identifier"));
+      assert_eq!(token.assert_usable().unwrap_err(), StdError::generic_err("Empty Token identifier"));
+    }
+    
+    assert!(Fungible::Coin("uluna".to_string()).assert_usable().is_ok());
+    assert!(Fungible::Token(Addr::unchecked(" token ")).assert_usable().is_ok());
+  }
+  
+  #[test]
+  fn test_reject_oversized() {
+    let at_limit = "a".repeat(MAX_IDENTIFIER_LEN);
+    let fungible: Fungible = from_json(format!(r#"{{"Coin":"{}"}}"#, at_limit).as_bytes()).unwrap();
+    assert_eq!(fungible, Fungible::Coin(at_limit.clone()));
+    
+    let over_limit = format!("{}a", at_limit);
+    let err = from_json::<Fungible>(format!(r#"{{"Coin":"{}"}}"#, over_limit).as_bytes()).unwrap_err();
+    assert!(err.to_string().contains("Coin identifier too long: 257 > 256 bytes"), "{}", err);
+    
+    let err = from_json::<Fungible>(format!(r#"{{"Token":"{}"}}"#, over_limit).as_bytes()).unwrap_err();
+    assert!(err.to_string().contains("Token identifier too long"), "{}", err);
+  }
+  
+  #[test]
+  fn test_validate_all() {
+    let api = MockApi::default();
+    let items = vec![
+      FungibleUnchecked::Coin("uluna".to_string()),
+      FungibleUnchecked::Token("token".to_string()),
+    ];
+    
+    assert_eq!(validate_all(&api, &items).unwrap(), vec![
+      Fungible::Coin("uluna".to_string()),
+      Fungible::Token(Addr::unchecked("token")),
+    ]);
+    assert_eq!(validate_all(&api, &[]).unwrap(), vec![]);
+  }
+  
+  #[test]
+  fn test_validate_all_errors() {
+    use crate::FungibleKind;
+    
+    let api = MockApi::default();
+    let items = vec![
+      FungibleUnchecked::Token("INVALID".to_string()),
+      FungibleUnchecked::Coin("uluna".to_string()),
+      FungibleUnchecked::Coin(" ".to_string()),
+      FungibleUnchecked::Token("token".to_string()),
+      FungibleUnchecked::Token("x".to_string()),
+    ];
+    
+    assert_eq!(validate_all(&api, &items).unwrap_err(), vec![
+      (0, FungibleError::InvalidAddress { address: "INVALID".to_string() }),
+      (2, FungibleError::EmptyIdentifier { kind: FungibleKind::Coin }),
+      (4, FungibleError::InvalidAddress { address: "x".to_string() }),
+    ]);
+  }
+  
+  #[test]
+  fn test_trusted_state() {
+    // state is trusted: deserializing a `Fungible` does not validate its address
+    let fungible: Fungible = from_json(br#"{"Token":"INVALID"}"#).unwrap();
+    assert_eq!(fungible, Fungible::Token(Addr::unchecked("INVALID")));
+  }
+  
+  #[test]
+  fn test_reject_empty() {
+    let err = from_json::<Fungible>(br#"{"Token":"  "}"#).unwrap_err();
+    assert!(err.to_string().contains("Empty Token identifier"), "{}", err);
+    
+    let err = from_json::<Fungible>(br#"{"Coin":""}"#).unwrap_err();
+    assert!(err.to_string().contains("Empty Coin identifier"), "{}", err);
+    
+    // messages may still carry them
+    assert_eq!(from_json::<FungibleUnchecked>(br#"{"Coin":""}"#).unwrap(), FungibleUnchecked::Coin("".to_string()));
+  }
+}